@@ -0,0 +1,212 @@
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Index;
+
+use bit_slice::Iter;
+use BitStorage;
+use BitOrder;
+use Lsb0;
+use BitSlice;
+use BitSliceMut;
+
+use TRUE;
+use FALSE;
+
+/// A fixed-size array of `N` bits stored inline in `W` storage elements, without any heap allocation.
+///
+/// Stable Rust cannot size the array from `N` alone, so `W` has to be passed as well and must equal
+/// `N.div_ceil(S::storage_size())`, for example `BitArray<u8, 12, 2>` or `BitArray<u64, 100, 2>`. Any other `W` is
+/// rejected at compile time:
+///
+/// ```compile_fail
+/// use bit_vector::BitArray;
+///
+/// let array = BitArray::<u8, 12, 1>::new(false);
+/// ```
+#[derive(Clone,Copy)]
+pub struct BitArray<S: BitStorage, const N: usize, const W: usize, O: BitOrder = Lsb0> {
+    data: [S; W],
+    order: PhantomData<O>
+}
+
+impl<S: BitStorage, const N: usize, const W: usize, O: BitOrder> BitArray<S, N, W, O> {
+    const WORD_COUNT_MATCHES: () = assert!(W == N.div_ceil(8 * mem::size_of::<S>()), "W does not match the number of storage elements needed for N bits");
+
+    pub fn new(default: bool) -> BitArray<S, N, W, O> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::WORD_COUNT_MATCHES;
+        let default = if default { S::MAX } else { S::ZERO };
+        let mut bit_array = BitArray {
+            data: [default; W],
+            order: PhantomData
+        };
+        bit_array.clear_unused_bits();
+        bit_array
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.as_bitslice().get(index)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        self.as_bitslice_mut().set(index, value);
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.as_bitslice().count_ones()
+    }
+
+    pub fn split_at(&self, index: usize) -> (BitSlice<'_, S, O>, BitSlice<'_, S, O>) {
+        self.as_bitslice().split_at(index)
+    }
+
+    pub fn split_at_mut(&mut self, index: usize) -> (BitSliceMut<'_, S, O>, BitSliceMut<'_, S, O>) {
+        self.as_bitslice_mut().split_at_mut(index)
+    }
+
+    pub fn as_bitslice(&self) -> BitSlice<'_, S, O> {
+        unsafe { BitSlice::from_pointer(self.data.as_ptr(), N) }
+    }
+
+    pub fn as_bitslice_mut(&mut self) -> BitSliceMut<'_, S, O> {
+        unsafe { BitSliceMut::from_pointer(self.data.as_mut_ptr(), N) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, S, O> {
+        self.as_bitslice().iter()
+    }
+
+    #[inline]
+    pub(crate) fn as_words(&self) -> &[S] {
+        &self.data
+    }
+
+    #[inline]
+    fn clear_unused_bits(&mut self) {
+        let (data_index, remainder) = S::compute_data_index_and_remainder(N);
+        if data_index < W {
+            self.data[data_index] &= O::first_bits_mask(remainder);
+        }
+    }
+}
+
+impl<S: BitStorage, const N: usize, const W: usize, O: BitOrder> Index<usize> for BitArray<S, N, W, O> {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &bool {
+        bool_ref!(self.as_bitslice()[index])
+    }
+}
+
+impl<'a, S: BitStorage + 'a, const N: usize, const W: usize, O: BitOrder> IntoIterator for &'a BitArray<S, N, W, O> {
+    type Item = bool;
+    type IntoIter = Iter<'a, S, O>;
+
+    fn into_iter(self) -> Iter<'a, S, O> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Msb0;
+    use super::BitArray;
+
+    #[test]
+    fn test_new() {
+        let array_8_16_false = BitArray::<u8, 16, 2>::new(false);
+        assert!(array_8_16_false.iter().all(|x| !x));
+
+        let array_8_16_true = BitArray::<u8, 16, 2>::new(true);
+        assert!(array_8_16_true.iter().all(|x| x));
+        assert_eq!(array_8_16_true.count_ones(), 16);
+
+        let array_32_100_true = BitArray::<u32, 100, 4>::new(true);
+        assert_eq!(array_32_100_true.capacity(), 100);
+        assert_eq!(array_32_100_true.iter().count(), 100);
+        assert_eq!(array_32_100_true.count_ones(), 100);
+
+        let array_8_0 = BitArray::<u8, 0, 0>::new(true);
+        assert_eq!(array_8_0.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut array = BitArray::<u8, 14, 2>::new(false);
+
+        array.set(0, true);
+        array.set(3, true);
+        array.set(8, true);
+        array.set(13, true);
+
+        assert_eq!(array.get(0), Some(true));
+        assert_eq!(array.get(1), Some(false));
+        assert_eq!(array.get(3), Some(true));
+        assert_eq!(array.get(8), Some(true));
+        assert_eq!(array.get(13), Some(true));
+        assert_eq!(array.get(14), None);
+
+        assert_eq!(array[0], true);
+        assert_eq!(array[12], false);
+        assert_eq!(array.capacity(), 14);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_out_of_bounds() {
+        let mut array = BitArray::<u8, 14, 2>::new(false);
+        array.set(14, true);
+    }
+
+    #[test]
+    fn test_msb0() {
+        let mut array = BitArray::<u8, 12, 2, Msb0>::new(true);
+        assert_eq!(array.as_words(), [0b11111111, 0b11110000]);
+
+        array.set(0, false);
+        array.set(11, false);
+        assert_eq!(array.as_words(), [0b01111111, 0b11100000]);
+    }
+
+    #[test]
+    fn test_copy() {
+        let mut array = BitArray::<u64, 64, 1>::new(false);
+        let copy = array;
+        array.set(5, true);
+
+        assert_eq!(array[5], true);
+        assert_eq!(copy[5], false);
+    }
+
+    #[test]
+    fn test_as_bitslice() {
+        let mut array = BitArray::<u8, 16, 2>::new(false);
+        array.as_bitslice_mut().set(9, true);
+
+        let slice = array.as_bitslice();
+        assert_eq!(slice.capacity(), 16);
+        assert_eq!(slice[9], true);
+        assert_eq!(slice[8], false);
+    }
+
+    #[test]
+    fn test_split_at_mut() {
+        let mut array = BitArray::<u8, 16, 2>::new(false);
+        {
+            let (mut left, mut right) = array.split_at_mut(8);
+            left.set(1, true);
+            right.set(1, true);
+        }
+
+        let iter_vec: Vec<_> = (&array).into_iter().collect();
+        assert_eq!(iter_vec, [false, true, false, false, false, false, false, false, false, true, false, false, false, false, false, false]);
+
+        let (left, right) = array.split_at(8);
+        assert_eq!(left.capacity(), 8);
+        assert_eq!(right[1], true);
+    }
+}
@@ -0,0 +1,339 @@
+use std::cmp;
+use std::fmt;
+
+use BitStorage;
+use BitVector;
+use BitSlice;
+use BitSliceMut;
+
+const BLOCK_SIZE: usize = 64;
+
+/// A `rows` by `cols` grid of bits stored row after row in a single `BitVector`.
+///
+/// Every row is padded to a whole number of storage elements, so rows can be borrowed as slices and combined a word
+/// at a time. The padding bits are kept at zero, writing to them through `row_mut` leaves the matrix in an invalid
+/// state.
+#[derive(Clone)]
+pub struct BitMatrix<S: BitStorage> {
+    bits: BitVector<S>,
+    rows: usize,
+    cols: usize
+}
+
+impl<S: BitStorage> BitMatrix<S> {
+    pub fn new(rows: usize, cols: usize) -> BitMatrix<S> {
+        let row_words = cols.div_ceil(S::storage_size());
+        BitMatrix {
+            bits: BitVector::with_capacity(rows * row_words * S::storage_size(), false),
+            rows,
+            cols
+        }
+    }
+
+    pub fn identity(size: usize) -> BitMatrix<S> {
+        let mut matrix = BitMatrix::new(size, size);
+        for index in 0..size {
+            matrix.set(index, index, true);
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<bool> {
+        match row < self.rows && col < self.cols {
+            true => Some(self.bits[row * self.row_stride() + col]),
+            false => None
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        self.panic_index_out_of_bounds(row, col);
+        let stride = self.row_stride();
+        self.bits.set(row * stride + col, value);
+    }
+
+    pub fn row(&self, row: usize) -> BitSlice<'_, S> {
+        self.panic_row_out_of_bounds(row);
+        let (_, rest) = self.bits.split_at(row * self.row_stride());
+        BitSlice::from_slice_with_len(&rest.as_raw_slice()[..self.row_words()], self.cols)
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> BitSliceMut<'_, S> {
+        self.panic_row_out_of_bounds(row);
+        let (row_words, cols) = (self.row_words(), self.cols);
+        let (_, rest) = self.bits.split_at_mut(row * row_words * S::storage_size());
+        BitSliceMut::from_slice_mut_with_len(&mut rest.into_raw_mut_slice()[..row_words], cols)
+    }
+
+    pub fn column(&self, col: usize) -> Column<'_, S> {
+        if col >= self.cols {
+            panic!("Column out of bounds. Columns = {}, Column = {}", self.cols, col);
+        }
+        Column {
+            matrix: self,
+            col,
+            row: 0
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    /// Transposes the matrix in blocks of 64 by 64 bits, each of which is transposed with word operations.
+    pub fn transpose(&self) -> BitMatrix<S> {
+        let mut transposed = BitMatrix::new(self.cols, self.rows);
+        let mut block = [0u64; BLOCK_SIZE];
+        for block_row in (0..self.rows).step_by(BLOCK_SIZE) {
+            for block_col in (0..self.cols).step_by(BLOCK_SIZE) {
+                let (block_rows, block_cols) = (cmp::min(BLOCK_SIZE, self.rows - block_row), cmp::min(BLOCK_SIZE, self.cols - block_col));
+                for (offset, block_word) in block.iter_mut().enumerate() {
+                    *block_word = if offset < block_rows { self.row(block_row + offset).get_bits(block_col, block_cols) } else { 0 };
+                }
+                transpose_block(&mut block);
+                for (offset, &block_word) in block.iter().enumerate().take(block_cols) {
+                    transposed.row_mut(block_col + offset).set_bits(block_row, block_rows, block_word);
+                }
+            }
+        }
+        transposed
+    }
+
+    /// Returns the boolean product, where an element is set if any pair of `self[row][k]` and `other[k][col]` is set.
+    pub fn multiply(&self, other: &BitMatrix<S>) -> BitMatrix<S> {
+        if self.cols != other.rows {
+            panic!("Matrix dimensions do not match. Columns = {}, Rows = {}", self.cols, other.rows);
+        }
+        let mut product = BitMatrix::new(self.rows, other.cols);
+        for row in 0..self.rows {
+            let mut product_row = product.row_mut(row);
+            let product_words = product_row.as_raw_mut_slice();
+            for k in self.row(row).iter_ones() {
+                for (product_word, &word) in product_words.iter_mut().zip(other.row(k).as_raw_slice()) {
+                    *product_word |= word;
+                }
+            }
+        }
+        product
+    }
+
+    #[inline]
+    fn row_words(&self) -> usize {
+        self.cols.div_ceil(S::storage_size())
+    }
+
+    #[inline]
+    fn row_stride(&self) -> usize {
+        self.row_words() * S::storage_size()
+    }
+
+    #[inline]
+    fn panic_index_out_of_bounds(&self, row: usize, col: usize) {
+        if row >= self.rows || col >= self.cols {
+            panic!("Index out of bounds. Rows = {}, Columns = {}, Row = {}, Column = {}", self.rows, self.cols, row, col);
+        }
+    }
+
+    #[inline]
+    fn panic_row_out_of_bounds(&self, row: usize) {
+        if row >= self.rows {
+            panic!("Row out of bounds. Rows = {}, Row = {}", self.rows, row);
+        }
+    }
+}
+
+impl<S: BitStorage> PartialEq for BitMatrix<S> {
+    fn eq(&self, other: &BitMatrix<S>) -> bool {
+        self.rows == other.rows && self.cols == other.cols && self.bits.as_words() == other.bits.as_words()
+    }
+}
+
+impl<S: BitStorage> Eq for BitMatrix<S> {}
+
+impl<S: BitStorage> fmt::Debug for BitMatrix<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[")?;
+        for row in 0..self.rows {
+            write!(f, "{} ", self.row(row))?;
+        }
+        write!(f, "| {}x{}]", self.rows, self.cols)
+    }
+}
+
+/// Iterates over the bits of a column from the first row to the last.
+#[derive(Clone,Debug)]
+pub struct Column<'a, S: BitStorage + 'a> {
+    matrix: &'a BitMatrix<S>,
+    col: usize,
+    row: usize
+}
+
+impl<'a, S: BitStorage + 'a> Iterator for Column<'a, S> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        let value = self.matrix.get(self.row, self.col)?;
+        self.row += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.matrix.rows - self.row;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, S: BitStorage + 'a> ExactSizeIterator for Column<'a, S> {}
+
+// swaps the off-diagonal halves, then the off-diagonal quarters of each half and so on, bit `col` of `block[row]` is
+// the element at (row, col)
+fn transpose_block(block: &mut [u64; BLOCK_SIZE]) {
+    let mut width = BLOCK_SIZE / 2;
+    let mut mask: u64 = 0x0000_0000_ffff_ffff;
+    while width != 0 {
+        let mut row = 0;
+        while row < BLOCK_SIZE {
+            let swap = ((block[row] >> width) ^ block[row + width]) & mask;
+            block[row] ^= swap << width;
+            block[row + width] ^= swap;
+            row = (row + width + 1) & !width;
+        }
+        width /= 2;
+        mask ^= mask << width;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitMatrix;
+
+    fn create_matrix_u8_3x10() -> BitMatrix<u8> {
+        let mut matrix = BitMatrix::new(3, 10);
+        matrix.set(0, 0, true);
+        matrix.set(0, 9, true);
+        matrix.set(1, 4, true);
+        matrix.set(2, 8, true);
+        matrix.set(2, 9, true);
+        matrix
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let mut matrix = create_matrix_u8_3x10();
+        assert_eq!(matrix.get(0, 9), Some(true));
+        assert_eq!(matrix.get(1, 9), Some(false));
+        assert_eq!(matrix.get(3, 0), None);
+        assert_eq!(matrix.get(0, 10), None);
+        assert_eq!(matrix.count_ones(), 5);
+
+        matrix.set(0, 9, false);
+        assert_eq!(matrix.get(0, 9), Some(false));
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds. Rows = 3, Columns = 10, Row = 1, Column = 10")]
+    fn test_set_out_of_bounds() {
+        create_matrix_u8_3x10().set(1, 10, true);
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut matrix = create_matrix_u8_3x10();
+        assert_eq!(matrix.row(0).capacity(), 10);
+        assert_eq!(matrix.row(0).as_raw_slice(), [0b00000001, 0b00000010]);
+        assert_eq!(matrix.row(2).as_raw_slice(), [0b00000000, 0b00000011]);
+
+        matrix.row_mut(1).set(5, true);
+        assert_eq!(matrix.get(1, 5), Some(true));
+        assert_eq!(matrix.row(1).iter_ones().collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(format!("{:?}", matrix), "[1000000001 0000110000 0000000011 | 3x10]");
+    }
+
+    #[test]
+    #[should_panic(expected = "Row out of bounds. Rows = 3, Row = 3")]
+    fn test_row_out_of_bounds() {
+        create_matrix_u8_3x10().row(3);
+    }
+
+    #[test]
+    fn test_column() {
+        let matrix = create_matrix_u8_3x10();
+        assert_eq!(matrix.column(9).collect::<Vec<_>>(), [true, false, true]);
+        assert_eq!(matrix.column(4).len(), 3);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let matrix = create_matrix_u8_3x10();
+        let transposed = matrix.transpose();
+        assert_eq!(transposed.rows(), 10);
+        assert_eq!(transposed.cols(), 3);
+        for row in 0..3 {
+            for col in 0..10 {
+                assert_eq!(transposed.get(col, row), matrix.get(row, col));
+            }
+        }
+        assert_eq!(transposed.transpose(), matrix);
+    }
+
+    #[test]
+    fn test_transpose_large() {
+        let mut matrix = BitMatrix::<u32>::new(130, 75);
+        for row in 0..130 {
+            for col in 0..75 {
+                if (row * 7 + col * 3) % 5 == 0 {
+                    matrix.set(row, col, true);
+                }
+            }
+        }
+        let transposed = matrix.transpose();
+        for row in 0..130 {
+            for col in 0..75 {
+                assert_eq!(transposed.get(col, row), matrix.get(row, col));
+            }
+        }
+        assert_eq!(transposed.count_ones(), matrix.count_ones());
+    }
+
+    #[test]
+    fn test_multiply() {
+        // edges 0 -> 1, 1 -> 2, 2 -> 3
+        let mut adjacency = BitMatrix::<u64>::new(4, 4);
+        adjacency.set(0, 1, true);
+        adjacency.set(1, 2, true);
+        adjacency.set(2, 3, true);
+
+        let two_steps = adjacency.multiply(&adjacency);
+        assert_eq!(two_steps.get(0, 2), Some(true));
+        assert_eq!(two_steps.get(1, 3), Some(true));
+        assert_eq!(two_steps.count_ones(), 2);
+
+        let identity = BitMatrix::identity(4);
+        assert_eq!(adjacency.multiply(&identity), adjacency);
+
+        // reachability by repeated squaring of the adjacency matrix with self loops
+        let mut reachable = adjacency.clone();
+        for index in 0..4 {
+            reachable.set(index, index, true);
+        }
+        for _ in 0..2 {
+            reachable = reachable.multiply(&reachable);
+        }
+        assert_eq!(reachable.row(0).count_ones(), 4);
+        assert_eq!(reachable.get(3, 0), Some(false));
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrix dimensions do not match. Columns = 10, Rows = 3")]
+    fn test_multiply_dimensions_do_not_match() {
+        let matrix = create_matrix_u8_3x10();
+        matrix.multiply(&matrix);
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt::Debug;
+
+use BitStorage;
+
+/// Determines which bit of a storage element holds which index.
+pub trait BitOrder: Copy + Debug {
+    /// Identifies the order in serialized formats.
+    const ORDER_ID: u8;
+
+    /// Maps an index within a storage element to the bit position passed to `BitStorage::get` and `BitStorage::set`.
+    fn storage_index<S: BitStorage>(remainder: u32) -> u32;
+
+    /// Returns a mask selecting the first `count` indices of a storage element.
+    fn first_bits_mask<S: BitStorage>(count: u32) -> S;
+
+    /// Returns the shift that moves the byte holding indices `8 * byte_index..8 * byte_index + 8` of a storage element
+    /// into the lowest byte, keeping those indices in the same order within the byte.
+    fn byte_shift<S: BitStorage>(byte_index: usize) -> u32;
+
+    /// Shifts a storage element so that index `count` ends up at index 0, dropping the indices before it.
+    fn shift_to_front<S: BitStorage>(element: S, count: u32) -> S;
+
+    /// Returns the number of unset indices before the first set index of a storage element.
+    fn count_first_zeros<S: BitStorage>(element: S) -> usize;
+
+    /// Returns the indices `remainder..remainder + count` of a storage element in its lowest `count` bits, keeping
+    /// them in the order of their storage bits.
+    fn get_field<S: BitStorage>(element: S, remainder: usize, count: usize) -> S;
+
+    /// Replaces the indices `remainder..remainder + count` of a storage element with the lowest `count` bits of
+    /// `field`, the inverse of `get_field`.
+    fn set_field<S: BitStorage>(element: S, remainder: usize, count: usize, field: S) -> S;
+
+    /// Returns the shift of the `count` indices starting at `offset` within a value of `len` indices read by
+    /// `get_bits`. The first index is the least significant bit of the value for `Lsb0` and the most significant
+    /// bit for `Msb0`.
+    fn field_shift(offset: usize, count: usize, len: usize) -> usize;
+}
+
+/// Index 0 is the least significant bit of each storage element.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,Hash)]
+pub struct Lsb0;
+
+/// Index 0 is the most significant bit of each storage element.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,Hash)]
+pub struct Msb0;
+
+impl BitOrder for Lsb0 {
+    const ORDER_ID: u8 = 0;
+
+    #[inline]
+    fn storage_index<S: BitStorage>(remainder: u32) -> u32 {
+        remainder
+    }
+
+    #[inline]
+    fn first_bits_mask<S: BitStorage>(count: u32) -> S {
+        S::mask(0, count as usize)
+    }
+
+    #[inline]
+    fn byte_shift<S: BitStorage>(byte_index: usize) -> u32 {
+        (byte_index * 8) as u32
+    }
+
+    #[inline]
+    fn shift_to_front<S: BitStorage>(element: S, count: u32) -> S {
+        element >> count
+    }
+
+    #[inline]
+    fn count_first_zeros<S: BitStorage>(element: S) -> usize {
+        element.trailing_zeros() as usize
+    }
+
+    #[inline]
+    fn get_field<S: BitStorage>(element: S, remainder: usize, count: usize) -> S {
+        (element >> remainder as u32) & S::mask(0, count)
+    }
+
+    #[inline]
+    fn set_field<S: BitStorage>(element: S, remainder: usize, count: usize, field: S) -> S {
+        (element & !S::mask(remainder, remainder + count)) | (field << remainder as u32)
+    }
+
+    #[inline]
+    fn field_shift(offset: usize, _count: usize, _len: usize) -> usize {
+        offset
+    }
+}
+
+impl BitOrder for Msb0 {
+    const ORDER_ID: u8 = 1;
+
+    #[inline]
+    fn storage_index<S: BitStorage>(remainder: u32) -> u32 {
+        S::storage_size() as u32 - 1 - remainder
+    }
+
+    #[inline]
+    fn first_bits_mask<S: BitStorage>(count: u32) -> S {
+        S::mask(S::storage_size() - count as usize, S::storage_size())
+    }
+
+    #[inline]
+    fn byte_shift<S: BitStorage>(byte_index: usize) -> u32 {
+        (S::storage_size() - (byte_index + 1) * 8) as u32
+    }
+
+    #[inline]
+    fn shift_to_front<S: BitStorage>(element: S, count: u32) -> S {
+        element << count
+    }
+
+    #[inline]
+    fn count_first_zeros<S: BitStorage>(element: S) -> usize {
+        element.leading_zeros() as usize
+    }
+
+    // index `remainder + count - 1` is storage bit `S::storage_size() - remainder - count`
+    #[inline]
+    fn get_field<S: BitStorage>(element: S, remainder: usize, count: usize) -> S {
+        let shift = S::storage_size() - remainder - count;
+        (element >> shift as u32) & S::mask(0, count)
+    }
+
+    #[inline]
+    fn set_field<S: BitStorage>(element: S, remainder: usize, count: usize, field: S) -> S {
+        let shift = S::storage_size() - remainder - count;
+        (element & !S::mask(shift, shift + count)) | (field << shift as u32)
+    }
+
+    #[inline]
+    fn field_shift(offset: usize, count: usize, len: usize) -> usize {
+        len - offset - count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitOrder,Lsb0,Msb0};
+
+    #[test]
+    fn test_storage_index() {
+        assert_eq!(Lsb0::storage_index::<u8>(0), 0);
+        assert_eq!(Lsb0::storage_index::<u8>(7), 7);
+        assert_eq!(Msb0::storage_index::<u8>(0), 7);
+        assert_eq!(Msb0::storage_index::<u8>(7), 0);
+        assert_eq!(Msb0::storage_index::<u32>(1), 30);
+    }
+
+    #[test]
+    fn test_byte_shift() {
+        assert_eq!(Lsb0::byte_shift::<u8>(0), 0);
+        assert_eq!(Lsb0::byte_shift::<u32>(1), 8);
+        assert_eq!(Lsb0::byte_shift::<u32>(3), 24);
+        assert_eq!(Msb0::byte_shift::<u8>(0), 0);
+        assert_eq!(Msb0::byte_shift::<u32>(0), 24);
+        assert_eq!(Msb0::byte_shift::<u32>(3), 0);
+    }
+
+    #[test]
+    fn test_first_bits_mask() {
+        assert_eq!(Lsb0::first_bits_mask::<u8>(0), 0b00000000);
+        assert_eq!(Lsb0::first_bits_mask::<u8>(3), 0b00000111);
+        assert_eq!(Lsb0::first_bits_mask::<u8>(7), 0b01111111);
+        assert_eq!(Msb0::first_bits_mask::<u8>(0), 0b00000000);
+        assert_eq!(Msb0::first_bits_mask::<u8>(3), 0b11100000);
+        assert_eq!(Msb0::first_bits_mask::<u8>(7), 0b11111110);
+    }
+
+    #[test]
+    fn test_shift_to_front() {
+        assert_eq!(Lsb0::shift_to_front(0b10110100u8, 2), 0b00101101);
+        assert_eq!(Msb0::shift_to_front(0b10110100u8, 2), 0b11010000);
+    }
+
+    #[test]
+    fn test_count_first_zeros() {
+        assert_eq!(Lsb0::count_first_zeros(0b00101000u8), 3);
+        assert_eq!(Msb0::count_first_zeros(0b00101000u8), 2);
+        assert_eq!(Lsb0::count_first_zeros(0u16), 16);
+        assert_eq!(Msb0::count_first_zeros(0u16), 16);
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(Lsb0::get_field(0b10110100u8, 2, 4), 0b1101);
+        assert_eq!(Msb0::get_field(0b10110100u8, 2, 4), 0b1101);
+        assert_eq!(Lsb0::get_field(0b10110100u8, 0, 8), 0b10110100);
+        assert_eq!(Lsb0::set_field(0b10110100u8, 2, 4, 0b0110), 0b10011000);
+        assert_eq!(Msb0::set_field(0b10110100u8, 2, 4, 0b0110), 0b10011000);
+        assert_eq!(Msb0::set_field(0u16, 0, 16, 0xabcd), 0xabcd);
+        assert_eq!(Lsb0::field_shift(3, 2, 10), 3);
+        assert_eq!(Msb0::field_shift(3, 2, 10), 5);
+    }
+}
@@ -0,0 +1,347 @@
+use std::cmp;
+use std::fmt;
+use std::iter::FromIterator;
+
+use BitStorage;
+use BitVector;
+
+/// A set of `usize` values backed by a `BitVector` that grows to fit the largest inserted value.
+#[derive(Clone)]
+pub struct BitSet<S: BitStorage> {
+    bit_vector: BitVector<S>
+}
+
+impl<S: BitStorage> BitSet<S> {
+    pub fn new() -> BitSet<S> {
+        BitSet::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> BitSet<S> {
+        BitSet {
+            bit_vector: BitVector::with_capacity(capacity, false)
+        }
+    }
+
+    pub fn insert(&mut self, value: usize) -> bool {
+        if value >= self.bit_vector.capacity() {
+            self.bit_vector.resize(value + 1, false);
+        }
+        let inserted = !self.bit_vector[value];
+        self.bit_vector.set(value, true);
+        inserted
+    }
+
+    pub fn remove(&mut self, value: usize) -> bool {
+        let removed = self.contains(value);
+        if removed {
+            self.bit_vector.set(value, false);
+        }
+        removed
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.bit_vector.get(value).unwrap_or(false)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bit_vector.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words().iter().all(|&element| element == S::ZERO)
+    }
+
+    pub fn clear(&mut self) {
+        self.bit_vector = BitVector::with_capacity(self.bit_vector.capacity(), false);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bit_vector.capacity()
+    }
+
+    pub fn iter(&self) -> Iter<'_, S> {
+        Iter {
+            ones: Ones::new(self.words(), &[], |left, _| left)
+        }
+    }
+
+    pub fn union<'a>(&'a self, other: &'a BitSet<S>) -> Union<'a, S> {
+        Union {
+            ones: Ones::new(self.words(), other.words(), |left, right| left | right)
+        }
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a BitSet<S>) -> Intersection<'a, S> {
+        Intersection {
+            ones: Ones::new(self.words(), other.words(), |left, right| left & right)
+        }
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a BitSet<S>) -> Difference<'a, S> {
+        Difference {
+            ones: Ones::new(self.words(), other.words(), |left, right| left & !right)
+        }
+    }
+
+    pub fn symmetric_difference<'a>(&'a self, other: &'a BitSet<S>) -> SymmetricDifference<'a, S> {
+        SymmetricDifference {
+            ones: Ones::new(self.words(), other.words(), |left, right| left ^ right)
+        }
+    }
+
+    pub fn is_subset(&self, other: &BitSet<S>) -> bool {
+        self.all_word_pairs(other, |left, right| left & !right == S::ZERO)
+    }
+
+    pub fn is_superset(&self, other: &BitSet<S>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet<S>) -> bool {
+        self.all_word_pairs(other, |left, right| left & right == S::ZERO)
+    }
+
+    #[inline]
+    fn words(&self) -> &[S] {
+        self.bit_vector.as_words()
+    }
+
+    // compares the sets element by element, treating the elements past the end of the shorter set as zero
+    #[inline]
+    fn all_word_pairs<F>(&self, other: &BitSet<S>, predicate: F) -> bool where F: Fn(S, S) -> bool {
+        let (left, right) = (self.words(), other.words());
+        let len = cmp::max(left.len(), right.len());
+        (0..len).all(|data_index| predicate(word_or_zero(left, data_index), word_or_zero(right, data_index)))
+    }
+}
+
+impl<S: BitStorage> Default for BitSet<S> {
+    fn default() -> BitSet<S> {
+        BitSet::new()
+    }
+}
+
+impl<S: BitStorage> PartialEq for BitSet<S> {
+    fn eq(&self, other: &BitSet<S>) -> bool {
+        self.all_word_pairs(other, |left, right| left == right)
+    }
+}
+
+impl<S: BitStorage> Eq for BitSet<S> {}
+
+impl<S: BitStorage> fmt::Debug for BitSet<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<S: BitStorage> FromIterator<usize> for BitSet<S> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> BitSet<S> {
+        let mut bit_set = BitSet::new();
+        bit_set.extend(iter);
+        bit_set
+    }
+}
+
+impl<S: BitStorage> Extend<usize> for BitSet<S> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, S: BitStorage + 'a> IntoIterator for &'a BitSet<S> {
+    type Item = usize;
+    type IntoIter = Iter<'a, S>;
+
+    fn into_iter(self) -> Iter<'a, S> {
+        self.iter()
+    }
+}
+
+#[inline]
+fn word_or_zero<S: BitStorage>(words: &[S], data_index: usize) -> S {
+    words.get(data_index).cloned().unwrap_or(S::ZERO)
+}
+
+// yields the indices of the bits set in the element-wise combination of two storage slices, in ascending order
+#[derive(Clone)]
+struct Ones<'a, S: BitStorage + 'a> {
+    left: &'a [S],
+    right: &'a [S],
+    combine: fn(S, S) -> S,
+    element: S,
+    data_index: usize
+}
+
+impl<'a, S: BitStorage + 'a> Ones<'a, S> {
+    fn new(left: &'a [S], right: &'a [S], combine: fn(S, S) -> S) -> Ones<'a, S> {
+        let element = combine(word_or_zero(left, 0), word_or_zero(right, 0));
+        Ones {
+            left,
+            right,
+            combine,
+            element,
+            data_index: 0
+        }
+    }
+}
+
+impl<'a, S: BitStorage + 'a> Iterator for Ones<'a, S> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if self.element != S::ZERO {
+                let remainder = self.element.trailing_zeros();
+                S::set(&mut self.element, remainder, false);
+                return Some((self.data_index * S::storage_size()) + remainder as usize);
+            }
+
+            self.data_index += 1;
+            if self.data_index >= cmp::max(self.left.len(), self.right.len()) {
+                return None;
+            }
+            self.element = (self.combine)(word_or_zero(self.left, self.data_index), word_or_zero(self.right, self.data_index));
+        }
+    }
+}
+
+macro_rules! set_iterator {
+    ($name:ident) => (
+        #[derive(Clone)]
+        pub struct $name<'a, S: BitStorage + 'a> {
+            ones: Ones<'a, S>
+        }
+
+        impl<'a, S: BitStorage + 'a> Iterator for $name<'a, S> {
+            type Item = usize;
+
+            fn next(&mut self) -> Option<usize> {
+                self.ones.next()
+            }
+        }
+    )
+}
+
+set_iterator!(Iter);
+set_iterator!(Union);
+set_iterator!(Intersection);
+set_iterator!(Difference);
+set_iterator!(SymmetricDifference);
+
+#[cfg(test)]
+mod tests {
+    use super::BitSet;
+
+    #[test]
+    fn test_insert_contains() {
+        let mut set = BitSet::<u8>::new();
+
+        assert_eq!(set.insert(3), true);
+        assert_eq!(set.insert(3), false);
+        assert_eq!(set.insert(100), true);
+
+        assert_eq!(set.contains(3), true);
+        assert_eq!(set.contains(100), true);
+        assert_eq!(set.contains(4), false);
+        assert_eq!(set.contains(1000), false);
+        assert_eq!(set.capacity(), 101);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: BitSet<u32> = [1, 5, 64].iter().cloned().collect();
+
+        assert_eq!(set.remove(5), true);
+        assert_eq!(set.remove(5), false);
+        assert_eq!(set.remove(500), false);
+        assert_eq!(set.contains(5), false);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_len_is_empty_clear() {
+        let mut set = BitSet::<u16>::new();
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+
+        set.extend(vec![0, 15, 16, 17, 200]);
+        assert_eq!(set.len(), 5);
+        assert!(!set.is_empty());
+
+        set.clear();
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_iter() {
+        let set: BitSet<u8> = vec![17, 0, 7, 8, 3, 8].into_iter().collect();
+
+        let set_iter_vec: Vec<_> = set.iter().collect();
+        assert_eq!(set_iter_vec, [0, 3, 7, 8, 17]);
+
+        let set_into_iter_vec: Vec<_> = (&set).into_iter().collect();
+        assert_eq!(set_into_iter_vec, [0, 3, 7, 8, 17]);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let left: BitSet<u8> = vec![1, 2, 3, 10, 20].into_iter().collect();
+        let right: BitSet<u8> = vec![2, 3, 4, 40].into_iter().collect();
+
+        let union: Vec<_> = left.union(&right).collect();
+        assert_eq!(union, [1, 2, 3, 4, 10, 20, 40]);
+
+        let intersection: Vec<_> = left.intersection(&right).collect();
+        assert_eq!(intersection, [2, 3]);
+
+        let difference: Vec<_> = left.difference(&right).collect();
+        assert_eq!(difference, [1, 10, 20]);
+
+        let difference: Vec<_> = right.difference(&left).collect();
+        assert_eq!(difference, [4, 40]);
+
+        let symmetric_difference: Vec<_> = left.symmetric_difference(&right).collect();
+        assert_eq!(symmetric_difference, [1, 4, 10, 20, 40]);
+    }
+
+    #[test]
+    fn test_subset_superset_disjoint() {
+        let small: BitSet<u8> = vec![2, 3].into_iter().collect();
+        let large: BitSet<u8> = vec![1, 2, 3, 30].into_iter().collect();
+        let other: BitSet<u8> = vec![4, 5, 31].into_iter().collect();
+        let empty = BitSet::<u8>::new();
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(large.is_superset(&small));
+        assert!(!small.is_superset(&large));
+        assert!(empty.is_subset(&small));
+        assert!(small.is_superset(&empty));
+
+        assert!(small.is_disjoint(&other));
+        assert!(large.is_disjoint(&other));
+        assert!(!small.is_disjoint(&large));
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut left = BitSet::<u8>::with_capacity(100);
+        left.insert(5);
+        let right: BitSet<u8> = vec![5].into_iter().collect();
+
+        assert_eq!(left, right);
+
+        left.insert(99);
+        assert!(left != right);
+    }
+
+    #[test]
+    fn test_debug() {
+        let set: BitSet<u8> = vec![1, 9, 4].into_iter().collect();
+        assert_eq!(format!("{:?}", set), "{1, 4, 9}");
+    }
+}
@@ -1,32 +1,53 @@
-use num;
-
+use std::cmp;
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::Index;
+use std::slice;
 
 use BitStorage;
+use BitOrder;
+use Lsb0;
 
 use TRUE;
 use FALSE;
 
-#[derive(Debug)]
-pub struct BitSlice<'a, S: BitStorage + 'a> {
+pub struct BitSlice<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
     pointer: *const S,
     capacity: usize,
-    phantom: PhantomData<&'a S>
+    phantom: PhantomData<&'a S>,
+    order: PhantomData<O>
 }
 
-unsafe impl<'a, S: BitStorage + 'a> Send for BitSlice<'a, S> {}
-unsafe impl<'a, S: BitStorage + 'a> Sync for BitSlice<'a, S> {}
-
-impl<'a, S: BitStorage + 'a> BitSlice<'a, S> {
-    pub unsafe fn from_pointer(pointer: *const S, capacity: usize) -> BitSlice<'a, S> {
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Send for BitSlice<'a, S, O> {}
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Sync for BitSlice<'a, S, O> {}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> BitSlice<'a, S, O> {
+    /// Creates a slice over `capacity` bits starting at `pointer`.
+    ///
+    /// # Safety
+    ///
+    /// `pointer` must point to enough initialized storage elements to hold `capacity` bits,
+    /// which must stay valid to be read for the lifetime `'a`.
+    pub unsafe fn from_pointer(pointer: *const S, capacity: usize) -> BitSlice<'a, S, O> {
         BitSlice {
-            pointer: pointer,
-            capacity: capacity,
-            phantom: PhantomData
+            pointer,
+            capacity,
+            phantom: PhantomData,
+            order: PhantomData
         }
     }
 
+    pub fn from_slice(data: &'a [S]) -> BitSlice<'a, S, O> {
+        unsafe { BitSlice::from_pointer(data.as_ptr(), data.len() * S::storage_size()) }
+    }
+
+    pub fn from_slice_with_len(data: &'a [S], capacity: usize) -> BitSlice<'a, S, O> {
+        if capacity > data.len() * S::storage_size() {
+            panic!("Capacity exceeds storage. Maximum capacity = {}, Capacity = {}", data.len() * S::storage_size(), capacity);
+        }
+        unsafe { BitSlice::from_pointer(data.as_ptr(), capacity) }
+    }
+
     pub fn get(&self, index: usize) -> Option<bool> {
         match self.index_in_bounds(index) {
             true => Some(self.get_unchecked(index)),
@@ -38,7 +59,7 @@ impl<'a, S: BitStorage + 'a> BitSlice<'a, S> {
         self.capacity
     }
 
-    pub fn split_at(self, index: usize) -> (BitSlice<'a, S>, BitSlice<'a, S>) {
+    pub fn split_at(self, index: usize) -> (BitSlice<'a, S, O>, BitSlice<'a, S, O>) {
         self.panic_index_not_on_storage_bound(index);
         let data_index = S::compute_data_index(index);
         let (capacity_left, capacity_right) = self.compute_capacities(index);
@@ -51,21 +72,68 @@ impl<'a, S: BitStorage + 'a> BitSlice<'a, S> {
         }
     }
 
-    pub fn reborrow<'b>(&'b self) -> BitSlice<'b, S> {
+    pub fn reborrow<'b>(&'b self) -> BitSlice<'b, S, O> {
         BitSlice {
             pointer: self.pointer,
             capacity: self.capacity,
-            phantom: self.phantom
+            phantom: self.phantom,
+            order: self.order
+        }
+    }
+
+    pub fn as_raw_slice(&self) -> &'a [S] {
+        unsafe { slice::from_raw_parts(self.pointer, self.compute_data_len()) }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.rank(self.capacity)
+    }
+
+    /// Returns the number of set bits before `index`.
+    pub fn rank(&self, index: usize) -> usize {
+        if index > self.capacity {
+            panic!("Index out of bounds. Length = {}, Index = {}", self.capacity, index);
+        }
+        // the bits past the capacity of a slice are not guaranteed to be zero, so the last element is masked
+        let (data_index, remainder) = S::compute_data_index_and_remainder(index);
+        let data = self.as_raw_slice();
+        let ones: usize = data[..data_index].iter().map(|&element| element.count_ones() as usize).sum();
+        if remainder == 0 {
+            ones
+        }
+        else {
+            ones + (data[data_index] & O::first_bits_mask(remainder)).count_ones() as usize
         }
     }
 
-    pub fn iter(&self) -> Iter<S> {
+    /// Returns the `len` bits starting at `start`, at most 64, as described by `BitOrder::field_shift`.
+    pub fn get_bits(&self, start: usize, len: usize) -> u64 {
+        panic_bits_out_of_bounds(self.capacity, start, len);
+        get_bits::<S, O>(self.as_raw_slice(), start, len)
+    }
+
+    pub fn iter_ones(&self) -> IterOnes<'a, S, O> {
+        IterOnes {
+            data: self.as_raw_slice(),
+            capacity: self.capacity,
+            data_index_counter: 0,
+            remainder_counter: 0,
+            order: PhantomData
+        }
+    }
+
+    pub fn iter_runs(&self) -> IterRuns<'a, S, O> {
+        IterRuns::new(self.as_raw_slice(), self.capacity)
+    }
+
+    pub fn iter(&self) -> Iter<'a, S, O> {
         Iter {
             pointer: self.pointer,
             capacity: self.capacity,
             data_index_counter: 0,
             remainder_counter: 0,
-            phantom: PhantomData
+            phantom: PhantomData,
+            order: PhantomData
         }
     }
 
@@ -76,9 +144,14 @@ impl<'a, S: BitStorage + 'a> BitSlice<'a, S> {
     }
 
     #[inline]
-    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: S) -> bool {
-        let element = unsafe { *self.pointer.offset(data_index as isize) };
-        S::get(&element, remainder)
+    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: u32) -> bool {
+        let element = unsafe { *self.pointer.add(data_index) };
+        S::get(&element, O::storage_index::<S>(remainder))
+    }
+
+    #[inline]
+    fn compute_data_len(&self) -> usize {
+        self.capacity.div_ceil(S::storage_size())
     }
 
     #[inline]
@@ -89,7 +162,7 @@ impl<'a, S: BitStorage + 'a> BitSlice<'a, S> {
     #[inline]
     fn compute_pointers(&self, data_index_to_split: usize) -> (*const S, *const S) {
         let pointer_left = self.pointer;
-        let pointer_right = unsafe { self.pointer.offset(data_index_to_split as isize) };
+        let pointer_right = unsafe { self.pointer.add(data_index_to_split) };
         (pointer_left, pointer_right)
     }
 
@@ -113,7 +186,51 @@ impl<'a, S: BitStorage + 'a> BitSlice<'a, S> {
     }
 }
 
-impl<'a, S: BitStorage + 'a> Index<usize> for BitSlice<'a, S> {
+// reads the field a storage element at a time, the caller checks the bounds
+pub(crate) fn get_bits<S: BitStorage, O: BitOrder>(data: &[S], start: usize, len: usize) -> u64 {
+    let mut value = 0;
+    let mut offset = 0;
+    while offset < len {
+        let (data_index, remainder) = S::compute_data_index_and_remainder(start + offset);
+        let remainder = remainder as usize;
+        let count = cmp::min(len - offset, S::storage_size() - remainder);
+        let field = O::get_field(data[data_index], remainder, count).to_u64();
+        value |= field << O::field_shift(offset, count, len);
+        offset += count;
+    }
+    value
+}
+
+pub(crate) fn set_bits<S: BitStorage, O: BitOrder>(data: &mut [S], start: usize, len: usize, value: u64) {
+    let mut offset = 0;
+    while offset < len {
+        let (data_index, remainder) = S::compute_data_index_and_remainder(start + offset);
+        let remainder = remainder as usize;
+        let count = cmp::min(len - offset, S::storage_size() - remainder);
+        let field = S::from_u64((value >> O::field_shift(offset, count, len)) & u64::mask(0, count));
+        data[data_index] = O::set_field(data[data_index], remainder, count, field);
+        offset += count;
+    }
+}
+
+#[inline]
+pub(crate) fn panic_bits_out_of_bounds(capacity: usize, start: usize, len: usize) {
+    if len > 64 {
+        panic!("Bit count out of range. Bit count = {}", len);
+    }
+    if start > capacity || len > capacity - start {
+        panic!("Range out of bounds. Length = {}, Start = {}, Bit count = {}", capacity, start, len);
+    }
+}
+
+#[inline]
+pub(crate) fn panic_value_exceeds_bits(len: usize, value: u64) {
+    if len < 64 && value >> len != 0 {
+        panic!("Value exceeds bit count. Bit count = {}, Value = {}", len, value);
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> Index<usize> for BitSlice<'a, S, O> {
     type Output = bool;
 
     fn index(&self, index: usize) -> &bool {
@@ -122,38 +239,44 @@ impl<'a, S: BitStorage + 'a> Index<usize> for BitSlice<'a, S> {
     }
 }
 
-#[derive(Debug)]
-pub struct Iter<'a, S: BitStorage + 'a> {
+pub struct Iter<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
     pointer: *const S,
     capacity: usize,
     data_index_counter: usize,
     remainder_counter: usize,
-    phantom: PhantomData<&'a S>
+    phantom: PhantomData<&'a S>,
+    order: PhantomData<O>
 }
 
-unsafe impl<'a, S: BitStorage + 'a> Send for Iter<'a, S> {}
-unsafe impl<'a, S: BitStorage + 'a> Sync for Iter<'a, S> {}
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Send for Iter<'a, S, O> {}
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Sync for Iter<'a, S, O> {}
 
-impl<'a, S: BitStorage + 'a> IntoIterator for &'a BitSlice<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> IntoIterator for &'a BitSlice<'a, S, O> {
     type Item = bool;
-    type IntoIter = Iter<'a, S>;
+    type IntoIter = Iter<'a, S, O>;
 
-    fn into_iter(self) -> Iter<'a, S> {
+    fn into_iter(self) -> Iter<'a, S, O> {
         self.iter()
     }
 }
 
-impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> fmt::Debug for Iter<'a, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Iter").field("index", &self.calculate_index()).field("len", &self.capacity).finish()
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iterator for Iter<'a, S, O> {
     type Item = bool;
 
     fn next(&mut self) -> Option<bool> {
-        let remainder: S = num::cast(self.remainder_counter).unwrap();
-        let next = self.get_unchecked_by_data_index_and_remainder(self.data_index_counter, remainder);
-
         if self.calculate_index() == self.capacity {
             return None;
         }
 
+        let remainder = self.remainder_counter as u32;
+        let next = self.get_unchecked_by_data_index_and_remainder(self.data_index_counter, remainder);
+
         self.remainder_counter += 1;
         if self.remainder_counter == S::storage_size() {
             self.remainder_counter = 0;
@@ -164,11 +287,11 @@ impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
     }
 }
 
-impl<'a, S: BitStorage + 'a> Iter<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iter<'a, S, O> {
     #[inline]
-    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: S) -> bool {
-        let element = unsafe { *self.pointer.offset(data_index as isize) };
-        S::get(&element, remainder)
+    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: u32) -> bool {
+        let element = unsafe { *self.pointer.add(data_index) };
+        S::get(&element, O::storage_index::<S>(remainder))
     }
 
     #[inline]
@@ -177,11 +300,111 @@ impl<'a, S: BitStorage + 'a> Iter<'a, S> {
     }
 }
 
+/// Iterates over the indices of the set bits.
+pub struct IterOnes<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
+    data: &'a [S],
+    capacity: usize,
+    data_index_counter: usize,
+    remainder_counter: usize,
+    order: PhantomData<O>
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> fmt::Debug for IterOnes<'a, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let index = (self.data_index_counter * S::storage_size()) + self.remainder_counter;
+        f.debug_struct("IterOnes").field("index", &index).field("len", &self.capacity).finish()
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iterator for IterOnes<'a, S, O> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.data_index_counter < self.data.len() {
+            // clears the bits before the current position, so the first set bit left is the next one
+            let remainder = self.remainder_counter as u32;
+            let element = self.data[self.data_index_counter] & !O::first_bits_mask::<S>(remainder);
+            if element == S::ZERO {
+                self.data_index_counter += 1;
+                self.remainder_counter = 0;
+                continue;
+            }
+
+            let remainder = O::count_first_zeros(element);
+            let index = (self.data_index_counter * S::storage_size()) + remainder;
+            if index >= self.capacity {
+                return None;
+            }
+
+            self.remainder_counter = remainder + 1;
+            if self.remainder_counter == S::storage_size() {
+                self.remainder_counter = 0;
+                self.data_index_counter += 1;
+            }
+            return Some(index);
+        }
+        None
+    }
+}
+
+/// Iterates over the runs of equal bits as `(value, length)` pairs.
+pub struct IterRuns<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
+    data: &'a [S],
+    capacity: usize,
+    index: usize,
+    order: PhantomData<O>
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> IterRuns<'a, S, O> {
+    pub(crate) fn new(data: &'a [S], capacity: usize) -> IterRuns<'a, S, O> {
+        IterRuns {
+            data,
+            capacity,
+            index: 0,
+            order: PhantomData
+        }
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> fmt::Debug for IterRuns<'a, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IterRuns").field("index", &self.index).field("len", &self.capacity).finish()
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iterator for IterRuns<'a, S, O> {
+    type Item = (bool, usize);
+
+    fn next(&mut self) -> Option<(bool, usize)> {
+        if self.index >= self.capacity {
+            return None;
+        }
+
+        let (data_index, remainder) = S::compute_data_index_and_remainder(self.index);
+        let value = S::get(&self.data[data_index], O::storage_index::<S>(remainder));
+        let start = self.index;
+        while self.index < self.capacity {
+            // a run of ones is a run of zeros in the inverted element
+            let (data_index, remainder) = S::compute_data_index_and_remainder(self.index);
+            let element = if value { !self.data[data_index] } else { self.data[data_index] };
+            let bits_left_in_element = S::storage_size() - (self.index - data_index * S::storage_size());
+            let run = O::count_first_zeros(O::shift_to_front(element, remainder));
+
+            if run < bits_left_in_element {
+                self.index = cmp::min(self.index + run, self.capacity);
+                break;
+            }
+            self.index = cmp::min(self.index + bits_left_in_element, self.capacity);
+        }
+        Some((value, self.index - start))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::{BitSlice,BitVector};
+    use super::super::{BitSlice,BitVector,Msb0};
 
-    fn create_bitslice_u8_16_from_bitvector_u8_32(vec: &BitVector<u8>) -> BitSlice<u8> {
+    fn create_bitslice_u8_16_from_bitvector_u8_32(vec: &BitVector<u8>) -> BitSlice<'_, u8> {
         let (_, right) = vec.split_at(16);
         right
     }
@@ -189,7 +412,7 @@ mod tests {
     #[test]
     fn test_from_pointer() {
         let vec: Vec<u8> = vec![0b11001111, 0b01001001];
-        let slice = unsafe { BitSlice::from_pointer(vec.as_ptr(), 16) };
+        let slice: BitSlice<u8> = unsafe { BitSlice::from_pointer(vec.as_ptr(), 16) };
 
         assert_eq!(slice[0], true);
         assert_eq!(slice[1], true);
@@ -210,6 +433,122 @@ mod tests {
         assert_eq!(slice[15], false);
     }
 
+    #[test]
+    fn test_from_pointer_msb0() {
+        let vec: Vec<u8> = vec![0b11001111, 0b01001001];
+        let slice: BitSlice<u8, Msb0> = unsafe { BitSlice::from_pointer(vec.as_ptr(), 16) };
+
+        let slice_iter_vec: Vec<_> = slice.iter().collect();
+        assert_eq!(slice_iter_vec, [true, true, false, false, true, true, true, true, false, true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let data: [u8; 2] = [0b11001111, 0b01001001];
+        let slice: BitSlice<u8> = BitSlice::from_slice(&data);
+
+        assert_eq!(slice.capacity(), 16);
+        assert_eq!(slice[0], true);
+        assert_eq!(slice[4], false);
+        assert_eq!(slice[14], true);
+        assert_eq!(slice[15], false);
+    }
+
+    #[test]
+    fn test_from_slice_with_len() {
+        let data: [u8; 2] = [0b11001111, 0b01001001];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 12);
+
+        assert_eq!(slice.capacity(), 12);
+        assert_eq!(slice[11], true);
+        assert_eq!(slice.get(12), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_slice_with_len_exceeding_storage() {
+        let data: [u8; 2] = [0b11001111, 0b01001001];
+        BitSlice::<u8>::from_slice_with_len(&data, 17);
+    }
+
+    #[test]
+    fn test_as_raw_slice() {
+        let data: [u8; 3] = [0b11001111, 0b01001001, 0b00000001];
+
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 12);
+        assert_eq!(slice.as_raw_slice(), [0b11001111, 0b01001001]);
+
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 16);
+        assert_eq!(slice.as_raw_slice(), [0b11001111, 0b01001001]);
+
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 0);
+        assert!(slice.as_raw_slice().is_empty());
+
+        let vec = BitVector::<u8>::with_capacity(20, true);
+        let (_, right) = vec.split_at(8);
+        assert_eq!(right.as_raw_slice(), [0b11111111, 0b00001111]);
+    }
+
+    #[test]
+    fn test_count_ones_and_rank() {
+        let data: [u8; 3] = [0b11001111, 0b01001001, 0b11111111];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 12);
+
+        assert_eq!(slice.count_ones(), 8);
+        assert_eq!(slice.rank(0), 0);
+        assert_eq!(slice.rank(4), 4);
+        assert_eq!(slice.rank(8), 6);
+        assert_eq!(slice.rank(12), 8);
+
+        let slice: BitSlice<u8, Msb0> = BitSlice::from_slice_with_len(&data, 12);
+        assert_eq!(slice.count_ones(), 7);
+        assert_eq!(slice.rank(2), 2);
+        assert_eq!(slice.rank(9), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rank_out_of_bounds() {
+        let data: [u8; 2] = [0b11001111, 0b01001001];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 12);
+        slice.rank(13);
+    }
+
+    #[test]
+    fn test_iter_ones() {
+        let data: [u8; 3] = [0b11001111, 0b00000000, 0b10000001];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 20);
+        let ones: Vec<_> = slice.iter_ones().collect();
+        assert_eq!(ones, [0, 1, 2, 3, 6, 7, 16]);
+
+        let slice: BitSlice<u8, Msb0> = BitSlice::from_slice(&data);
+        let ones: Vec<_> = slice.iter_ones().collect();
+        assert_eq!(ones, [0, 1, 4, 5, 6, 7, 16, 23]);
+    }
+
+    #[test]
+    fn test_iter_runs() {
+        let data: [u8; 3] = [0b11001111, 0b00000000, 0b11111111];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 20);
+        let runs: Vec<_> = slice.iter_runs().collect();
+        assert_eq!(runs, [(true, 4), (false, 2), (true, 2), (false, 8), (true, 4)]);
+
+        let slice: BitSlice<u8, Msb0> = BitSlice::from_slice(&data);
+        let runs: Vec<_> = slice.iter_runs().collect();
+        assert_eq!(runs, [(true, 2), (false, 2), (true, 4), (false, 8), (true, 8)]);
+
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 0);
+        assert_eq!(slice.iter_runs().count(), 0);
+    }
+
+    #[test]
+    fn test_iter_runs_across_elements() {
+        let data: [u32; 3] = [0xffff0000, 0xffffffff, 0x00000001];
+        let slice: BitSlice<u32> = BitSlice::from_slice_with_len(&data, 90);
+        let runs: Vec<_> = slice.iter_runs().collect();
+        assert_eq!(runs, [(false, 16), (true, 49), (false, 25)]);
+    }
+
     #[test]
     fn test_index_bits_already_set() {
         let mut vec_8_32: BitVector<u8> = BitVector::with_capacity(32, false);
@@ -276,7 +615,7 @@ mod tests {
         let vec_8_32: BitVector<u8> = BitVector::with_capacity(32, false);
         let slice = create_bitslice_u8_16_from_bitvector_u8_32(&vec_8_32);
 
-        slice[16];
+        let _ = slice[16];
     }
 
     #[test]
@@ -423,6 +762,35 @@ mod tests {
         assert_eq!(vec_8_16_iter_vec, left_plus_right_iter_vec);
     }
 
+    #[test]
+    fn test_iter_stops_at_end_of_storage() {
+        let vec: Vec<u8> = vec![0b11001111, 0b01001001];
+        let slice: BitSlice<u8> = unsafe { BitSlice::from_pointer(vec.as_ptr(), 16) };
+
+        assert_eq!(slice.iter().count(), 16);
+        assert_eq!(slice.iter().filter(|&x| x).count(), 9);
+    }
+
+    #[test]
+    fn test_get_bits() {
+        let data: [u8; 3] = [0b10000000, 0b01010111, 0b00000011];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 20);
+        assert_eq!(slice.get_bits(5, 13), 0x1abc);
+        assert_eq!(slice.get_bits(16, 4), 0b0011);
+
+        let data: [u8; 2] = [0x06, 0xaf];
+        let slice: BitSlice<u8, Msb0> = BitSlice::from_slice(&data);
+        assert_eq!(slice.get_bits(5, 11), 0b11010101111);
+    }
+
+    #[test]
+    #[should_panic(expected = "Range out of bounds. Length = 20, Start = 10, Bit count = 11")]
+    fn test_get_bits_out_of_bounds() {
+        let data: [u8; 3] = [0, 0, 0];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 20);
+        slice.get_bits(10, 11);
+    }
+
     #[test]
     fn test_into_iter_on_reference() {
         let mut vec_8_4 = BitVector::<u8>::with_capacity(4, false);
@@ -469,13 +837,13 @@ mod tests {
     fn test_derive_debug() {
         let vec: BitVector<u32> = BitVector::with_capacity(32, true);
         let (_, slice) = vec.split_at(0);
-        format!("{:?}", slice);
+        let _ = format!("{:?}", slice);
     }
 
     #[test]
     fn test_derive_iter_debug() {
         let vec: BitVector<u32> = BitVector::with_capacity(32, true);
         let (_, slice) = vec.split_at(0);
-        format!("{:?}", slice.iter());
+        let _ = format!("{:?}", slice.iter());
     }
 }
\ No newline at end of file
@@ -1,33 +1,55 @@
-use num;
-
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::Index;
+use std::slice;
 
 use BitStorage;
+use BitOrder;
+use Lsb0;
 use BitSlice;
+use bit_slice;
+use bit_slice::IterRuns;
 
 use TRUE;
 use FALSE;
 
-#[derive(Debug)]
-pub struct BitSliceMut<'a, S: BitStorage + 'a> {
+pub struct BitSliceMut<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
     pointer: *mut S,
     capacity: usize,
-    phantom: PhantomData<&'a mut S>
+    phantom: PhantomData<&'a mut S>,
+    order: PhantomData<O>
 }
 
-unsafe impl<'a, S: BitStorage + 'a> Send for BitSliceMut<'a, S> {}
-unsafe impl<'a, S: BitStorage + 'a> Sync for BitSliceMut<'a, S> {}
-
-impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
-    pub unsafe fn from_pointer(pointer: *mut S, capacity: usize) -> BitSliceMut<'a, S> {
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Send for BitSliceMut<'a, S, O> {}
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Sync for BitSliceMut<'a, S, O> {}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> BitSliceMut<'a, S, O> {
+    /// Creates a slice over `capacity` bits starting at `pointer`.
+    ///
+    /// # Safety
+    ///
+    /// `pointer` must point to enough initialized storage elements to hold `capacity` bits,
+    /// which must stay valid to be read and written for the lifetime `'a` and must not be accessed through any other pointer while the slice is alive.
+    pub unsafe fn from_pointer(pointer: *mut S, capacity: usize) -> BitSliceMut<'a, S, O> {
         BitSliceMut {
-            pointer: pointer,
-            capacity: capacity,
-            phantom: PhantomData
+            pointer,
+            capacity,
+            phantom: PhantomData,
+            order: PhantomData
         }
     }
 
+    pub fn from_slice_mut(data: &'a mut [S]) -> BitSliceMut<'a, S, O> {
+        unsafe { BitSliceMut::from_pointer(data.as_mut_ptr(), data.len() * S::storage_size()) }
+    }
+
+    pub fn from_slice_mut_with_len(data: &'a mut [S], capacity: usize) -> BitSliceMut<'a, S, O> {
+        if capacity > data.len() * S::storage_size() {
+            panic!("Capacity exceeds storage. Maximum capacity = {}, Capacity = {}", data.len() * S::storage_size(), capacity);
+        }
+        unsafe { BitSliceMut::from_pointer(data.as_mut_ptr(), capacity) }
+    }
+
     pub fn get(&self, index: usize) -> Option<bool> {
         match self.index_in_bounds(index) {
             true => Some(self.get_unchecked(index)),
@@ -39,16 +61,28 @@ impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
         self.panic_index_bounds(index);
         let (data_index, remainder) = S::compute_data_index_and_remainder(index);
         unsafe {
-            let element_pointer = self.pointer.offset(data_index as isize);
-            S::set(&mut *element_pointer, remainder, value);
+            let element_pointer = self.pointer.add(data_index);
+            S::set(&mut *element_pointer, O::storage_index::<S>(remainder), value);
         }
     }
 
+    pub fn get_bits(&self, start: usize, len: usize) -> u64 {
+        bit_slice::panic_bits_out_of_bounds(self.capacity, start, len);
+        bit_slice::get_bits::<S, O>(self.as_raw_slice(), start, len)
+    }
+
+    /// Replaces the `len` bits starting at `start` with `value`, the inverse of `get_bits`.
+    pub fn set_bits(&mut self, start: usize, len: usize, value: u64) {
+        bit_slice::panic_bits_out_of_bounds(self.capacity, start, len);
+        bit_slice::panic_value_exceeds_bits(len, value);
+        bit_slice::set_bits::<S, O>(self.as_raw_mut_slice(), start, len, value);
+    }
+
     pub fn capacity(&self) -> usize {
         self.capacity
     }
 
-    pub fn split_at(self, index: usize) -> (BitSlice<'a, S>, BitSlice<'a, S>) {
+    pub fn split_at(self, index: usize) -> (BitSlice<'a, S, O>, BitSlice<'a, S, O>) {
         self.panic_index_not_on_storage_bound(index);
         let data_index = S::compute_data_index(index);
         let (capacity_left, capacity_right) = self.compute_capacities(index);
@@ -61,7 +95,7 @@ impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
         }
     }
 
-    pub fn split_at_mut(self, index: usize) -> (BitSliceMut<'a, S>, BitSliceMut<'a, S>) {
+    pub fn split_at_mut(self, index: usize) -> (BitSliceMut<'a, S, O>, BitSliceMut<'a, S, O>) {
         self.panic_index_not_on_storage_bound(index);
         let data_index = S::compute_data_index(index);
         let (capacity_left, capacity_right) = self.compute_capacities(index);
@@ -74,21 +108,51 @@ impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
         }
     }
 
-    pub fn reborrow<'b>(&'b self) -> BitSliceMut<'b, S> {
+    /// Borrows the slice mutably for a shorter lifetime, so only one reborrow can be alive at a time:
+    ///
+    /// ```compile_fail
+    /// use bit_vector::BitVector;
+    ///
+    /// let mut vec: BitVector<u8> = BitVector::with_capacity(8, false);
+    /// let mut slice = vec.as_bitslice_mut();
+    /// let mut first = slice.reborrow();
+    /// let mut second = slice.reborrow();
+    /// first.as_raw_mut_slice()[0] = 1;
+    /// second.as_raw_mut_slice()[0] = 2;
+    /// ```
+    pub fn reborrow<'b>(&'b mut self) -> BitSliceMut<'b, S, O> {
         BitSliceMut {
             pointer: self.pointer,
             capacity: self.capacity,
-            phantom: self.phantom
+            phantom: self.phantom,
+            order: self.order
         }
     }
 
-    pub fn iter(&self) -> Iter<S> {
+    pub fn as_raw_slice(&self) -> &[S] {
+        unsafe { slice::from_raw_parts(self.pointer, self.compute_data_len()) }
+    }
+
+    pub fn as_raw_mut_slice(&mut self) -> &mut [S] {
+        unsafe { slice::from_raw_parts_mut(self.pointer, self.compute_data_len()) }
+    }
+
+    pub fn into_raw_mut_slice(self) -> &'a mut [S] {
+        unsafe { slice::from_raw_parts_mut(self.pointer, self.compute_data_len()) }
+    }
+
+    pub fn iter_runs(&self) -> IterRuns<'_, S, O> {
+        IterRuns::new(self.as_raw_slice(), self.capacity)
+    }
+
+    pub fn iter(&self) -> Iter<'_, S, O> {
         Iter {
             pointer: self.pointer,
             capacity: self.capacity,
             data_index_counter: 0,
             remainder_counter: 0,
-            phantom: PhantomData
+            phantom: PhantomData,
+            order: PhantomData
         }
     }
 
@@ -99,9 +163,14 @@ impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
     }
 
     #[inline]
-    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: S) -> bool {
-        let element = unsafe { *self.pointer.offset(data_index as isize) };
-        S::get(&element, remainder)
+    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: u32) -> bool {
+        let element = unsafe { *self.pointer.add(data_index) };
+        S::get(&element, O::storage_index::<S>(remainder))
+    }
+
+    #[inline]
+    fn compute_data_len(&self) -> usize {
+        self.capacity.div_ceil(S::storage_size())
     }
 
     #[inline]
@@ -112,7 +181,7 @@ impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
     #[inline]
     fn compute_pointers(&self, data_index_to_split: usize) -> (*const S, *const S) {
         let pointer_left = self.pointer;
-        let pointer_right = unsafe { self.pointer.offset(data_index_to_split as isize) };
+        let pointer_right = unsafe { self.pointer.add(data_index_to_split) };
         (pointer_left, pointer_right)
     }
 
@@ -143,7 +212,7 @@ impl<'a, S: BitStorage + 'a> BitSliceMut<'a, S> {
     }
 }
 
-impl<'a, S: BitStorage + 'a> Index<usize> for BitSliceMut<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> Index<usize> for BitSliceMut<'a, S, O> {
     type Output = bool;
 
     fn index(&self, index: usize) -> &bool {
@@ -152,38 +221,44 @@ impl<'a, S: BitStorage + 'a> Index<usize> for BitSliceMut<'a, S> {
     }
 }
 
-#[derive(Debug)]
-pub struct Iter<'a, S: BitStorage + 'a> {
+pub struct Iter<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
     pointer: *mut S,
     capacity: usize,
     data_index_counter: usize,
     remainder_counter: usize,
-    phantom: PhantomData<&'a S>
+    phantom: PhantomData<&'a S>,
+    order: PhantomData<O>
 }
 
-unsafe impl<'a, S: BitStorage + 'a> Send for Iter<'a, S> {}
-unsafe impl<'a, S: BitStorage + 'a> Sync for Iter<'a, S> {}
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Send for Iter<'a, S, O> {}
+unsafe impl<'a, S: BitStorage + 'a, O: BitOrder> Sync for Iter<'a, S, O> {}
 
-impl<'a, S: BitStorage + 'a> IntoIterator for &'a BitSliceMut<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> IntoIterator for &'a BitSliceMut<'a, S, O> {
     type Item = bool;
-    type IntoIter = Iter<'a, S>;
+    type IntoIter = Iter<'a, S, O>;
 
-    fn into_iter(self) -> Iter<'a, S> {
+    fn into_iter(self) -> Iter<'a, S, O> {
         self.iter()
     }
 }
 
-impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> fmt::Debug for Iter<'a, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Iter").field("index", &self.calculate_index()).field("len", &self.capacity).finish()
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iterator for Iter<'a, S, O> {
     type Item = bool;
 
     fn next(&mut self) -> Option<bool> {
-        let remainder: S = num::cast(self.remainder_counter).unwrap();
-        let next = self.get_unchecked_by_data_index_and_remainder(self.data_index_counter, remainder);
-
         if self.calculate_index() == self.capacity {
             return None;
         }
 
+        let remainder = self.remainder_counter as u32;
+        let next = self.get_unchecked_by_data_index_and_remainder(self.data_index_counter, remainder);
+
         self.remainder_counter += 1;
         if self.remainder_counter == S::storage_size() {
             self.remainder_counter = 0;
@@ -194,11 +269,11 @@ impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
     }
 }
 
-impl<'a, S: BitStorage + 'a> Iter<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iter<'a, S, O> {
     #[inline]
-    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: S) -> bool {
-        let element = unsafe { *self.pointer.offset(data_index as isize) };
-        S::get(&element, remainder)
+    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: u32) -> bool {
+        let element = unsafe { *self.pointer.add(data_index) };
+        S::get(&element, O::storage_index::<S>(remainder))
     }
 
     #[inline]
@@ -209,9 +284,9 @@ impl<'a, S: BitStorage + 'a> Iter<'a, S> {
 
 #[cfg(test)]
 mod tests {
-    use super::super::{BitSliceMut,BitVector};
+    use super::super::{BitSliceMut,BitVector,Msb0};
 
-    fn create_bitslice_mut_u8_16_from_bitvector_u8_32(vec: &mut BitVector<u8>) -> BitSliceMut<u8> {
+    fn create_bitslice_mut_u8_16_from_bitvector_u8_32(vec: &mut BitVector<u8>) -> BitSliceMut<'_, u8> {
         let (_, right) = vec.split_at_mut(16);
         right
     }
@@ -219,7 +294,7 @@ mod tests {
     #[test]
     fn test_from_pointer() {
         let mut vec: Vec<u8> = vec![0b11001111, 0b01001001];
-        let slice = unsafe { BitSliceMut::from_pointer(vec.as_mut_ptr(), 16) };
+        let slice: BitSliceMut<u8> = unsafe { BitSliceMut::from_pointer(vec.as_mut_ptr(), 16) };
 
         assert_eq!(slice[0], true);
         assert_eq!(slice[1], true);
@@ -240,6 +315,53 @@ mod tests {
         assert_eq!(slice[15], false);
     }
 
+    #[test]
+    fn test_from_slice_mut() {
+        let mut data: [u8; 2] = [0b11001111, 0b01001001];
+        {
+            let mut slice: BitSliceMut<u8> = BitSliceMut::from_slice_mut(&mut data);
+            assert_eq!(slice.capacity(), 16);
+            assert_eq!(slice[0], true);
+
+            slice.set(0, false);
+            slice.set(15, true);
+        }
+
+        assert_eq!(data, [0b11001110, 0b11001001]);
+    }
+
+    #[test]
+    fn test_from_slice_mut_with_len() {
+        let mut data: [u8; 2] = [0b11001111, 0b01001001];
+        let slice: BitSliceMut<u8> = BitSliceMut::from_slice_mut_with_len(&mut data, 9);
+
+        assert_eq!(slice.capacity(), 9);
+        assert_eq!(slice[8], true);
+        assert_eq!(slice.get(9), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_slice_mut_with_len_exceeding_storage() {
+        let mut data: [u8; 2] = [0b11001111, 0b01001001];
+        BitSliceMut::<u8>::from_slice_mut_with_len(&mut data, 17);
+    }
+
+    #[test]
+    fn test_as_raw_mut_slice() {
+        let mut data: [u8; 3] = [0b11001111, 0b01001001, 0b00000001];
+        {
+            let mut slice: BitSliceMut<u8> = BitSliceMut::from_slice_mut_with_len(&mut data, 12);
+            assert_eq!(slice.as_raw_slice(), [0b11001111, 0b01001001]);
+
+            slice.as_raw_mut_slice()[1] = 0b11111111;
+            assert_eq!(slice[8], true);
+            assert_eq!(slice[9], true);
+        }
+
+        assert_eq!(data, [0b11001111, 0b11111111, 0b00000001]);
+    }
+
     #[test]
     fn test_index_bits_already_set() {
         let mut vec_8_32: BitVector<u8> = BitVector::with_capacity(32, false);
@@ -332,6 +454,20 @@ mod tests {
         assert_eq!(slice.get(15).unwrap(), true);
     }
 
+    #[test]
+    fn test_set_msb0() {
+        let mut vec: Vec<u8> = vec![0b00000000, 0b00000000];
+        {
+            let mut slice: BitSliceMut<u8, Msb0> = unsafe { BitSliceMut::from_pointer(vec.as_mut_ptr(), 16) };
+            slice.set(0, true);
+            slice.set(6, true);
+            slice.set(9, true);
+            slice.set(15, true);
+        }
+
+        assert_eq!(vec, [0b10000010, 0b01000001]);
+    }
+
     #[test]
     fn test_repeated_set() {
         let mut vec_8_32: BitVector<u8> = BitVector::with_capacity(32, false);
@@ -425,7 +561,7 @@ mod tests {
         let mut vec_8_32: BitVector<u8> = BitVector::with_capacity(32, false);
         let slice = create_bitslice_mut_u8_16_from_bitvector_u8_32(&mut vec_8_32);
 
-        slice[16];
+        let _ = slice[16];
     }
 
     #[test]
@@ -607,6 +743,16 @@ mod tests {
         assert_eq!(reborrow[15], false);
     }
 
+    #[test]
+    fn test_iter_runs() {
+        let mut data: [u8; 2] = [0b00001111, 0b11111110];
+        let mut slice: BitSliceMut<u8> = BitSliceMut::from_slice_mut_with_len(&mut data, 14);
+        slice.set(8, true);
+
+        let runs: Vec<_> = slice.iter_runs().collect();
+        assert_eq!(runs, [(true, 4), (false, 4), (true, 6)]);
+    }
+
     #[test]
     fn test_iter() {
         let mut vec_8_4 = BitVector::<u8>::with_capacity(4, false);
@@ -650,6 +796,25 @@ mod tests {
         assert_eq!(vec_8_16_iter_vec, left_plus_right_iter_vec);
     }
 
+    #[test]
+    fn test_get_and_set_bits() {
+        let mut data: [u8; 3] = [0b11111111, 0b00000000, 0b11111111];
+        {
+            let mut slice: BitSliceMut<u8> = BitSliceMut::from_slice_mut_with_len(&mut data, 20);
+            slice.set_bits(5, 13, 0x1abc);
+            assert_eq!(slice.get_bits(5, 13), 0x1abc);
+        }
+        assert_eq!(data, [0b10011111, 0b01010111, 0b11111111]);
+
+        let mut data: [u16; 2] = [0, 0];
+        {
+            let mut slice: BitSliceMut<u16, Msb0> = BitSliceMut::from_slice_mut(&mut data);
+            slice.set_bits(12, 8, 0xa5);
+            assert_eq!(slice.get_bits(12, 8), 0xa5);
+        }
+        assert_eq!(data, [0x000a, 0x5000]);
+    }
+
     #[test]
     fn test_into_iter_on_reference() {
         let mut vec_8_4 = BitVector::<u8>::with_capacity(4, false);
@@ -698,13 +863,13 @@ mod tests {
     fn test_derive_debug() {
         let mut vec: BitVector<u32> = BitVector::with_capacity(32, true);
         let (_, slice) = vec.split_at_mut(0);
-        format!("{:?}", slice);
+        let _ = format!("{:?}", slice);
     }
 
     #[test]
     fn test_derive_iter_debug() {
         let mut vec: BitVector<u32> = BitVector::with_capacity(32, true);
         let (_, slice) = vec.split_at_mut(0);
-        format!("{:?}", slice.iter());
+        let _ = format!("{:?}", slice.iter());
     }
 }
\ No newline at end of file
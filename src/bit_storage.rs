@@ -1,9 +1,17 @@
 use std::mem;
 use std::ops::{BitAnd,BitAndAssign,BitOr,BitOrAssign,BitXor,BitXorAssign,Not,Shl,ShlAssign,Shr,ShrAssign};
-use num;
-use num::{One,Zero,Unsigned,NumCast,Bounded};
 
-pub trait BitStorage: Sized +
+pub(crate) mod private {
+    pub trait Sealed {}
+}
+
+/// The storage element of the bit containers, implemented for `u8`, `u16`, `u32`, `u64`, `u128` and `usize`, and for
+/// `U64x4` with the `simd` feature.
+///
+/// The trait is sealed so that the word operations can be forwarded to the methods of the primitive types, which
+/// compile down to single instructions where the target has them. Bit positions within an element are `u32`, like
+/// the shift amounts of the primitive types.
+pub trait BitStorage: private::Sealed + Sized +
     BitAnd<Self, Output = Self> +
     BitAndAssign<Self> +
     BitOr<Self, Output = Self> +
@@ -11,77 +19,158 @@ pub trait BitStorage: Sized +
     BitXor<Self, Output = Self> +
     BitXorAssign<Self> +
     Not<Output = Self> +
-    Shl<Self, Output = Self> +
-    ShlAssign<Self> +
-    Shr<Self, Output = Self> +
-    ShrAssign<Self> +
-    Eq + Zero + One + Unsigned + NumCast + Bounded + Copy {
+    Shl<u32, Output = Self> +
+    ShlAssign<u32> +
+    Shr<u32, Output = Self> +
+    ShrAssign<u32> +
+    Eq + Copy {
+        /// `[u8; N]` for an element of `N` bytes.
+        type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default + Copy;
+
+        const ZERO: Self;
+
+        const ONE: Self;
+
+        const MAX: Self;
+
         fn storage_size() -> usize;
 
-        fn set(storage: &mut Self, storage_index: Self, value: bool);
+        fn count_ones(self) -> u32;
 
-        fn get(storage: &Self, storage_index: Self) -> bool;
+        fn leading_zeros(self) -> u32;
 
-        fn compute_data_index(index: usize) -> usize;
+        fn trailing_zeros(self) -> u32;
 
-        fn compute_remainder(index: usize) -> Self;
+        fn swap_bytes(self) -> Self;
 
-        fn compute_data_index_and_remainder(index: usize) -> (usize, Self);
-}
+        fn reverse_bits(self) -> Self;
 
-impl<S> BitStorage for S where S: Sized +
-    BitAnd<S, Output = S> +
-    BitAndAssign<S> +
-    BitOr<S, Output = S> +
-    BitOrAssign<S> +
-    BitXor<S, Output = S> +
-    BitXorAssign<S> +
-    Not<Output = S> +
-    Shl<S, Output = S> +
-    ShlAssign<S> +
-    Shr<S, Output = S> +
-    ShrAssign<S> +
-    Eq + Zero + One + Unsigned + NumCast + Bounded + Copy {
-        #[inline]
-        fn storage_size() -> usize {
-            mem::size_of::<S>() * 8
-        }
+        fn to_le_bytes(self) -> Self::Bytes;
+
+        fn from_le_bytes(bytes: Self::Bytes) -> Self;
+
+        /// Returns the element with the storage bits `lo..hi` set, where bit 0 is the least significant bit.
+        fn mask(lo: usize, hi: usize) -> Self;
+
+        /// Returns the lowest 64 storage bits, the higher ones are dropped.
+        fn to_u64(self) -> u64;
+
+        /// Returns an element holding `value` in its lowest storage bits, the bits that do not fit are dropped.
+        fn from_u64(value: u64) -> Self;
 
         #[inline]
-        fn set(storage: &mut S, storage_index: S, value: bool) {
+        fn set(storage: &mut Self, storage_index: u32, value: bool) {
             if value {
-                *storage |= S::one() << storage_index;
+                *storage |= Self::ONE << storage_index;
             }
             else {
-                *storage &= !(S::one() << storage_index);
+                *storage &= !(Self::ONE << storage_index);
             }
         }
 
         #[inline]
-        fn get(storage: &S, storage_index: S) -> bool {
-            (*storage & (S::one() << storage_index)) != S::zero()
+        fn get(storage: &Self, storage_index: u32) -> bool {
+            (*storage & (Self::ONE << storage_index)) != Self::ZERO
         }
 
         #[inline]
         fn compute_data_index(index: usize) -> usize {
-            index / S::storage_size()
+            index / Self::storage_size()
         }
 
         #[inline]
-        fn compute_remainder(index: usize) -> S {
-            let remainder = index % S::storage_size();
-            // we know that remainder is always smaller or equal to the size that S can hold
-            // for example if S = u8 then remainder <= 2^8 - 1
-            let remainder: S = num::cast(remainder).unwrap();
-            remainder
+        fn compute_remainder(index: usize) -> u32 {
+            // the remainder is smaller than the storage size, which is at most 256
+            (index % Self::storage_size()) as u32
         }
 
         #[inline]
-        fn compute_data_index_and_remainder(index: usize) -> (usize, S) {
-            (S::compute_data_index(index), S::compute_remainder(index))
+        fn compute_data_index_and_remainder(index: usize) -> (usize, u32) {
+            (Self::compute_data_index(index), Self::compute_remainder(index))
         }
 }
 
+macro_rules! impl_bit_storage {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl BitStorage for $t {
+                type Bytes = [u8; mem::size_of::<$t>()];
+
+                const ZERO: $t = 0;
+
+                const ONE: $t = 1;
+
+                const MAX: $t = <$t>::MAX;
+
+                #[inline]
+                fn storage_size() -> usize {
+                    <$t>::BITS as usize
+                }
+
+                #[inline]
+                fn count_ones(self) -> u32 {
+                    <$t>::count_ones(self)
+                }
+
+                #[inline]
+                fn leading_zeros(self) -> u32 {
+                    <$t>::leading_zeros(self)
+                }
+
+                #[inline]
+                fn trailing_zeros(self) -> u32 {
+                    <$t>::trailing_zeros(self)
+                }
+
+                #[inline]
+                fn swap_bytes(self) -> $t {
+                    <$t>::swap_bytes(self)
+                }
+
+                #[inline]
+                fn reverse_bits(self) -> $t {
+                    <$t>::reverse_bits(self)
+                }
+
+                #[inline]
+                fn to_le_bytes(self) -> Self::Bytes {
+                    <$t>::to_le_bytes(self)
+                }
+
+                #[inline]
+                fn from_le_bytes(bytes: Self::Bytes) -> $t {
+                    <$t>::from_le_bytes(bytes)
+                }
+
+                #[inline]
+                fn mask(lo: usize, hi: usize) -> $t {
+                    if lo > hi || hi > <$t>::BITS as usize {
+                        panic!("Mask out of range. Storage size = {}, Low = {}, High = {}", <$t>::BITS, lo, hi);
+                    }
+                    if lo == hi {
+                        return 0;
+                    }
+                    (<$t>::MAX >> (<$t>::BITS as usize - (hi - lo))) << lo
+                }
+
+                #[inline]
+                fn to_u64(self) -> u64 {
+                    self as u64
+                }
+
+                #[inline]
+                fn from_u64(value: u64) -> $t {
+                    value as $t
+                }
+            }
+        )*
+    }
+}
+
+impl_bit_storage!(u8, u16, u32, u64, u128, usize);
+
 #[cfg(test)]
 mod tests {
     use super::BitStorage;
@@ -89,6 +178,8 @@ mod tests {
     #[test]
     fn test_storage_size() {
         assert_eq!(u8::storage_size(), 8);
+        assert_eq!(u128::storage_size(), 128);
+        assert_eq!(usize::storage_size(), usize::BITS as usize);
     }
 
     #[test]
@@ -145,4 +236,72 @@ mod tests {
         assert_eq!((1, 7), u8::compute_data_index_and_remainder(15));
         assert_eq!((2, 0), u8::compute_data_index_and_remainder(16));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_count_ones() {
+        assert_eq!(BitStorage::count_ones(0u8), 0);
+        assert_eq!(BitStorage::count_ones(0b10110001u8), 4);
+        assert_eq!(BitStorage::count_ones(u64::MAX), 64);
+        assert_eq!(BitStorage::count_ones(u128::MAX - 1), 127);
+    }
+
+    #[test]
+    fn test_trailing_zeros() {
+        assert_eq!(BitStorage::trailing_zeros(0u8), 8);
+        assert_eq!(BitStorage::trailing_zeros(1u8), 0);
+        assert_eq!(BitStorage::trailing_zeros(0b10110000u8), 4);
+        assert_eq!(BitStorage::trailing_zeros(0x80000000u32), 31);
+        assert_eq!(BitStorage::trailing_zeros(0x0000010000000000u64), 40);
+        assert_eq!(BitStorage::trailing_zeros(1u128 << 100), 100);
+    }
+
+    #[test]
+    fn test_leading_zeros() {
+        assert_eq!(BitStorage::leading_zeros(0u8), 8);
+        assert_eq!(BitStorage::leading_zeros(1u8), 7);
+        assert_eq!(BitStorage::leading_zeros(0b00101100u8), 2);
+        assert_eq!(BitStorage::leading_zeros(0x80000000u32), 0);
+        assert_eq!(BitStorage::leading_zeros(0x0000010000000000u64), 23);
+        assert_eq!(BitStorage::leading_zeros(1u128 << 100), 27);
+    }
+
+    #[test]
+    fn test_swap_bytes_and_reverse_bits() {
+        assert_eq!(BitStorage::swap_bytes(0x12u8), 0x12);
+        assert_eq!(BitStorage::swap_bytes(0x1234u16), 0x3412);
+        assert_eq!(BitStorage::reverse_bits(0b00010110u8), 0b01101000);
+        assert_eq!(BitStorage::reverse_bits(1u128), 1 << 127);
+    }
+
+    #[test]
+    fn test_le_bytes() {
+        let bytes = BitStorage::to_le_bytes(0x12345678u32);
+        assert_eq!(bytes, [0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(<u32 as BitStorage>::from_le_bytes(bytes), 0x12345678);
+        assert_eq!(<u128 as BitStorage>::from_le_bytes(BitStorage::to_le_bytes(u128::MAX - 5)), u128::MAX - 5);
+    }
+
+    #[test]
+    fn test_u64_conversions() {
+        assert_eq!(u8::from_u64(0x1234), 0x34);
+        assert_eq!(u128::from_u64(u64::MAX), u64::MAX as u128);
+        assert_eq!((u128::MAX - 1).to_u64(), u64::MAX - 1);
+        assert_eq!(BitStorage::to_u64(0xabu8), 0xab);
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(u8::mask(0, 0), 0);
+        assert_eq!(u8::mask(2, 5), 0b00011100);
+        assert_eq!(u8::mask(0, 8), 0b11111111);
+        assert_eq!(u8::mask(7, 8), 0b10000000);
+        assert_eq!(u64::mask(0, 64), u64::MAX);
+        assert_eq!(u128::mask(64, 128), u128::MAX << 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Mask out of range. Storage size = 8, Low = 3, High = 9")]
+    fn test_mask_out_of_range() {
+        u8::mask(3, 9);
+    }
+}
@@ -0,0 +1,491 @@
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::io::{self,Read,Write};
+use std::mem;
+use std::slice;
+
+use BitStorage;
+use BitOrder;
+use Lsb0;
+use BitVector;
+use BitSlice;
+use BitSliceMut;
+
+// values are laid out like `BitVector::set_bits` does, least significant bit first for `Lsb0` and most significant
+// bit first for `Msb0`
+
+/// Appends values of up to 64 bits and universal codes to a growing `BitVector`.
+///
+/// `write_unary(n)` writes `n` zeros and a one. `write_gamma` and `write_delta` write the Elias codes of values of at
+/// least 1, `write_rice(value, k)` writes `value >> k` in unary followed by the low `k` bits of `value`.
+#[derive(Clone,Debug)]
+pub struct BitWriter<S: BitStorage, O: BitOrder = Lsb0> {
+    bits: BitVector<S, O>
+}
+
+impl<S: BitStorage, O: BitOrder> BitWriter<S, O> {
+    pub fn new() -> BitWriter<S, O> {
+        BitWriter::from_bit_vector(BitVector::with_capacity(0, false))
+    }
+
+    /// Appends to the end of `bits`.
+    pub fn from_bit_vector(bits: BitVector<S, O>) -> BitWriter<S, O> {
+        BitWriter {
+            bits
+        }
+    }
+
+    /// Returns the number of bits written so far.
+    pub fn position(&self) -> usize {
+        self.bits.capacity()
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.bits.push(value);
+    }
+
+    pub fn write_bits(&mut self, value: u64, count: u32) {
+        if count > 64 {
+            panic!("Bit count out of range. Bit count = {}", count);
+        }
+        if count < 64 && value >> count != 0 {
+            panic!("Value exceeds bit count. Bit count = {}, Value = {}", count, value);
+        }
+        let start = self.bits.capacity();
+        self.bits.resize(start + count as usize, false);
+        self.bits.set_bits(start, count as usize, value);
+    }
+
+    pub fn write_unary(&mut self, value: u64) {
+        let start = self.bits.capacity();
+        self.bits.resize(start + value as usize, false);
+        self.bits.push(true);
+    }
+
+    pub fn write_gamma(&mut self, value: u64) {
+        let bit_count = Self::panic_not_positive(value);
+        self.write_unary(bit_count as u64 - 1);
+        self.write_bits(value & !(1 << (bit_count - 1)), bit_count - 1);
+    }
+
+    pub fn write_delta(&mut self, value: u64) {
+        let bit_count = Self::panic_not_positive(value);
+        self.write_gamma(bit_count as u64);
+        self.write_bits(value & !(1 << (bit_count - 1)), bit_count - 1);
+    }
+
+    pub fn write_rice(&mut self, value: u64, k: u32) {
+        if k >= 64 {
+            panic!("Rice parameter out of range. Parameter = {}", k);
+        }
+        self.write_unary(value >> k);
+        self.write_bits(value & ((1 << k) - 1), k);
+    }
+
+    pub fn as_bit_vector(&self) -> &BitVector<S, O> {
+        &self.bits
+    }
+
+    pub fn into_bit_vector(self) -> BitVector<S, O> {
+        self.bits
+    }
+
+    // returns the number of significant bits of `value`
+    #[inline]
+    fn panic_not_positive(value: u64) -> u32 {
+        if value == 0 {
+            panic!("Elias codes start at 1. Value = {}", value);
+        }
+        64 - value.leading_zeros()
+    }
+}
+
+impl<S: BitStorage, O: BitOrder> Default for BitWriter<S, O> {
+    fn default() -> BitWriter<S, O> {
+        BitWriter::new()
+    }
+}
+
+/// Reads back what `BitWriter` wrote.
+///
+/// Every read returns `BitReadError::UnexpectedEnd` when it runs past the end and `BitReadError::InvalidCode` when a
+/// code does not describe a `u64`. A failed read leaves the position unchanged.
+#[derive(Debug)]
+pub struct BitReader<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
+    bits: BitSlice<'a, S, O>,
+    position: usize
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> BitReader<'a, S, O> {
+    pub fn new(bits: BitSlice<'a, S, O>) -> BitReader<'a, S, O> {
+        BitReader {
+            bits,
+            position: 0
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bits.capacity() - self.position
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        if position > self.bits.capacity() {
+            panic!("Position out of bounds. Length = {}, Position = {}", self.bits.capacity(), position);
+        }
+        self.position = position;
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, BitReadError> {
+        self.check_remaining(1)?;
+        self.position += 1;
+        Ok(self.bits[self.position - 1])
+    }
+
+    pub fn read_bits(&mut self, count: u32) -> Result<u64, BitReadError> {
+        if count > 64 {
+            panic!("Bit count out of range. Bit count = {}", count);
+        }
+        self.check_remaining(count as usize)?;
+        let value = self.bits.get_bits(self.position, count as usize);
+        self.position += count as usize;
+        Ok(value)
+    }
+
+    pub fn read_unary(&mut self) -> Result<u64, BitReadError> {
+        let start = self.position;
+        while self.position < self.bits.capacity() {
+            self.position += 1;
+            if self.bits[self.position - 1] {
+                return Ok((self.position - start - 1) as u64);
+            }
+        }
+        let remaining_bits = self.position - start;
+        self.position = start;
+        Err(BitReadError::UnexpectedEnd { needed_bits: remaining_bits + 1, remaining_bits })
+    }
+
+    pub fn read_gamma(&mut self) -> Result<u64, BitReadError> {
+        let start = self.position;
+        self.read_restoring(start, |reader| {
+            let bit_count = reader.read_unary()? + 1;
+            reader.read_significant_bits(bit_count, start)
+        })
+    }
+
+    pub fn read_delta(&mut self) -> Result<u64, BitReadError> {
+        let start = self.position;
+        self.read_restoring(start, |reader| {
+            let bit_count = reader.read_gamma()?;
+            reader.read_significant_bits(bit_count, start)
+        })
+    }
+
+    pub fn read_rice(&mut self, k: u32) -> Result<u64, BitReadError> {
+        if k >= 64 {
+            panic!("Rice parameter out of range. Parameter = {}", k);
+        }
+        let start = self.position;
+        self.read_restoring(start, |reader| {
+            let quotient = reader.read_unary()?;
+            if quotient.leading_zeros() < k {
+                return Err(BitReadError::InvalidCode { position: start });
+            }
+            Ok((quotient << k) | reader.read_bits(k)?)
+        })
+    }
+
+    // reads the bits below the implicit leading one of a value with `bit_count` significant bits
+    fn read_significant_bits(&mut self, bit_count: u64, start: usize) -> Result<u64, BitReadError> {
+        if bit_count > 64 {
+            return Err(BitReadError::InvalidCode { position: start });
+        }
+        let low_bits = self.read_bits(bit_count as u32 - 1)?;
+        Ok((1 << (bit_count - 1)) | low_bits)
+    }
+
+    fn read_restoring<F: FnOnce(&mut Self) -> Result<u64, BitReadError>>(&mut self, start: usize, read: F) -> Result<u64, BitReadError> {
+        let result = read(self);
+        if result.is_err() {
+            self.position = start;
+        }
+        result
+    }
+
+    #[inline]
+    fn check_remaining(&self, count: usize) -> Result<(), BitReadError> {
+        if count > self.remaining() {
+            return Err(BitReadError::UnexpectedEnd { needed_bits: count, remaining_bits: self.remaining() });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone,Debug,PartialEq,Eq)]
+#[non_exhaustive]
+pub enum BitReadError {
+    UnexpectedEnd { needed_bits: usize, remaining_bits: usize },
+    InvalidCode { position: usize }
+}
+
+impl fmt::Display for BitReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BitReadError::UnexpectedEnd { needed_bits, remaining_bits } => write!(f, "Unexpected end of bits. Needed bits = {}, Remaining bits = {}", needed_bits, remaining_bits),
+            BitReadError::InvalidCode { position } => write!(f, "Invalid code. Position = {}", position)
+        }
+    }
+}
+
+impl Error for BitReadError {}
+
+/// Appends every byte as 8 bits, in the order the bits of a byte have in a `BitVector<u8, O>`, so that writing to a
+/// byte aligned position copies the bytes as they are.
+impl<O: BitOrder> Write for BitWriter<u8, O> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let capacity = self.bits.capacity();
+        if capacity % 8 == 0 {
+            let mut data = mem::replace(&mut self.bits, BitVector::with_capacity(0, false)).into_vec();
+            data.extend_from_slice(buf);
+            self.bits = BitVector::from_vec(data, capacity + buf.len() * 8);
+        }
+        else {
+            for byte in buf {
+                for bit in BitSlice::<u8, O>::from_slice(&[*byte]).iter() {
+                    self.bits.push(bit);
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads 8 bits at a time from the current position, which need not be byte aligned. Fewer than 8 remaining bits
+/// count as the end of the stream.
+impl<'a, O: BitOrder> Read for BitReader<'a, u8, O> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = cmp::min(buf.len(), self.remaining() / 8);
+        if self.position % 8 == 0 {
+            let start = self.position / 8;
+            buf[..count].copy_from_slice(&self.bits.as_raw_slice()[start..start + count]);
+        }
+        else {
+            for (byte_index, byte) in buf[..count].iter_mut().enumerate() {
+                let start = self.position + byte_index * 8;
+                let mut bits = BitSliceMut::<u8, O>::from_slice_mut(slice::from_mut(byte));
+                for index in 0..8 {
+                    bits.set(index, self.bits[start + index]);
+                }
+            }
+        }
+        self.position += count * 8;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self,Read,Write};
+
+    use super::super::{BitVector,Msb0};
+    use super::{BitReadError,BitReader,BitWriter};
+
+    #[test]
+    fn test_write_bits() {
+        let mut writer = BitWriter::<u8>::new();
+        writer.write_bits(0b101, 3);
+        writer.write_bool(true);
+        writer.write_bits(0x1234, 13);
+        assert_eq!(writer.position(), 17);
+
+        let bits = writer.into_bit_vector();
+        assert_eq!(bits.to_string(), "10110010110001001");
+
+        let mut reader = BitReader::new(bits.as_bitslice());
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        assert_eq!(reader.read_bool().unwrap(), true);
+        assert_eq!(reader.read_bits(13).unwrap(), 0x1234);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_codes() {
+        let mut writer = BitWriter::<u32>::new();
+        writer.write_unary(3);
+        writer.write_gamma(1);
+        writer.write_gamma(5);
+        writer.write_delta(1);
+        writer.write_delta(17);
+        writer.write_rice(11, 2);
+        assert_eq!(writer.as_bit_vector().to_string(), concat!("0001", "1", "00110", "1", "001101000", "00111"));
+
+        let bits = writer.into_bit_vector();
+        let mut reader = BitReader::new(bits.as_bitslice());
+        assert_eq!(reader.read_unary().unwrap(), 3);
+        assert_eq!(reader.read_gamma().unwrap(), 1);
+        assert_eq!(reader.read_gamma().unwrap(), 5);
+        assert_eq!(reader.read_delta().unwrap(), 1);
+        assert_eq!(reader.read_delta().unwrap(), 17);
+        assert_eq!(reader.read_rice(2).unwrap(), 11);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let values = [1u64, 2, 3, 7, 8, 1000, 123_456_789, u64::MAX >> 1, u64::MAX];
+        let mut writer = BitWriter::<u16, Msb0>::new();
+        for &value in &values {
+            writer.write_gamma(value);
+            writer.write_delta(value);
+            writer.write_bits(value, 64);
+            writer.write_rice(value >> 40, 5);
+        }
+
+        let bits = writer.into_bit_vector();
+        let mut reader = BitReader::new(bits.as_bitslice());
+        for &value in &values {
+            assert_eq!(reader.read_gamma().unwrap(), value);
+            assert_eq!(reader.read_delta().unwrap(), value);
+            assert_eq!(reader.read_bits(64).unwrap(), value);
+            assert_eq!(reader.read_rice(5).unwrap(), value >> 40);
+        }
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut writer = BitWriter::<u8>::new();
+        writer.write_bits(0xab, 8);
+        writer.write_bits(0xcd, 8);
+        let bits = writer.into_bit_vector();
+
+        let mut reader = BitReader::new(bits.as_bitslice());
+        reader.seek(8);
+        assert_eq!(reader.read_bits(8).unwrap(), 0xcd);
+        reader.seek(4);
+        assert_eq!(reader.position(), 4);
+        assert_eq!(reader.read_bits(8).unwrap(), 0xda);
+    }
+
+    #[test]
+    #[should_panic(expected = "Position out of bounds. Length = 0, Position = 1")]
+    fn test_seek_out_of_bounds() {
+        let bits = BitWriter::<u8>::new().into_bit_vector();
+        BitReader::new(bits.as_bitslice()).seek(1);
+    }
+
+    #[test]
+    fn test_read_past_end() {
+        let mut writer = BitWriter::<u8>::new();
+        writer.write_bits(0b11, 2);
+        writer.write_unary(4);
+        writer.write_bits(0, 2);
+        let bits = writer.into_bit_vector();
+
+        let mut reader = BitReader::new(bits.as_bitslice());
+        match reader.read_bits(10) {
+            Err(BitReadError::UnexpectedEnd { needed_bits: 10, remaining_bits: 9 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+        assert_eq!(reader.read_bits(2).unwrap(), 0b11);
+
+        // the gamma code announces 4 more bits but only 2 are left
+        match reader.read_gamma() {
+            Err(BitReadError::UnexpectedEnd { needed_bits: 4, remaining_bits: 2 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.read_unary().unwrap(), 4);
+        match reader.read_unary() {
+            Err(BitReadError::UnexpectedEnd { needed_bits: 3, remaining_bits: 2 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+        assert_eq!(reader.position(), 7);
+    }
+
+    #[test]
+    fn test_read_invalid_code() {
+        let mut writer = BitWriter::<u64>::new();
+        writer.write_unary(64);
+        writer.write_bits(0, 64);
+        let bits = writer.into_bit_vector();
+
+        let mut reader = BitReader::new(bits.as_bitslice());
+        match reader.read_gamma() {
+            Err(BitReadError::InvalidCode { position: 0 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+        assert_eq!(reader.position(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Elias codes start at 1. Value = 0")]
+    fn test_write_gamma_zero() {
+        BitWriter::<u8>::new().write_gamma(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Value exceeds bit count. Bit count = 3, Value = 8")]
+    fn test_write_bits_exceeds_count() {
+        BitWriter::<u8>::new().write_bits(8, 3);
+    }
+
+    #[test]
+    fn test_io_write() {
+        let mut writer = BitWriter::<u8>::new();
+        writer.write_all(&[0xb2, 0x01]).unwrap();
+        assert_eq!(writer.as_bit_vector().as_words(), [0xb2, 0x01]);
+
+        writer.write_bool(true);
+        write!(writer, "A").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.position(), 25);
+        assert_eq!(writer.as_bit_vector().as_words(), [0xb2, 0x01, 0b10000011, 0b00000000]);
+
+        let mut writer = BitWriter::<u8, Msb0>::new();
+        writer.write_bool(true);
+        writer.write_all(&[0xb2]).unwrap();
+        assert_eq!(writer.as_bit_vector().to_string(), "110110010");
+    }
+
+    #[test]
+    fn test_io_read() {
+        let bits = BitVector::<u8>::from_bytes(&[0xb2, 0x01, 0xff]);
+        let mut reader = BitReader::new(bits.as_bitslice());
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, [0xb2, 0x01, 0xff]);
+
+        let mut reader = BitReader::new(bits.as_bitslice());
+        reader.seek(4);
+        let mut bytes = [0u8; 2];
+        reader.read_exact(&mut bytes).unwrap();
+        assert_eq!(bytes, [0x1b, 0xf0]);
+        assert_eq!(reader.position(), 20);
+        assert_eq!(reader.read(&mut bytes).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_io_round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        for &offset in &[0, 3] {
+            let mut writer = BitWriter::<u8, Msb0>::new();
+            writer.write_bits(0, offset);
+            io::copy(&mut &data[..], &mut writer).unwrap();
+
+            let bits = writer.into_bit_vector();
+            let mut reader = BitReader::new(bits.as_bitslice());
+            reader.seek(offset as usize);
+            let mut read = vec![];
+            reader.read_to_end(&mut read).unwrap();
+            assert_eq!(read, data);
+        }
+    }
+}
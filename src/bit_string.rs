@@ -0,0 +1,328 @@
+use std::char;
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use BitStorage;
+use BitOrder;
+use BitVector;
+use BitSlice;
+use BitSliceMut;
+use BitArray;
+use SmallBitVector;
+
+// bits are written in index order, hex digits hold four bits each with the lowest index in the most significant bit,
+// so "0xb2" and "0b10110010" describe the same bits
+macro_rules! impl_bit_string_fmt {
+    ($bits:ty, $name:expr $(, const $param:ident)*) => {
+        impl<'a, S: BitStorage + 'a, O: BitOrder $(, const $param: usize)*> fmt::Debug for $bits {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt_debug(f, $name, self.capacity(), S::storage_size(), |index| self[index])
+            }
+        }
+
+        impl<'a, S: BitStorage + 'a, O: BitOrder $(, const $param: usize)*> fmt::Display for $bits {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.pad(&to_binary_string(self.iter()))
+            }
+        }
+
+        impl<'a, S: BitStorage + 'a, O: BitOrder $(, const $param: usize)*> fmt::Binary for $bits {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.pad_integral(true, "0b", &to_binary_string(self.iter()))
+            }
+        }
+
+        impl<'a, S: BitStorage + 'a, O: BitOrder $(, const $param: usize)*> fmt::LowerHex for $bits {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.pad_integral(true, "0x", &to_hex_string(self.iter(), false))
+            }
+        }
+
+        impl<'a, S: BitStorage + 'a, O: BitOrder $(, const $param: usize)*> fmt::UpperHex for $bits {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.pad_integral(true, "0x", &to_hex_string(self.iter(), true))
+            }
+        }
+    }
+}
+
+impl_bit_string_fmt!(BitVector<S, O>, "BitVector");
+impl_bit_string_fmt!(BitSlice<'a, S, O>, "BitSlice");
+impl_bit_string_fmt!(BitSliceMut<'a, S, O>, "BitSliceMut");
+impl_bit_string_fmt!(BitArray<S, N, W, O>, "BitArray", const N, const W);
+impl_bit_string_fmt!(SmallBitVector<S, INLINE, O>, "SmallBitVector", const INLINE);
+
+const DEBUG_MAX_WORDS: usize = 8;
+
+// prints the bits grouped by storage element, "{:?}" leaves out the middle elements of long vectors and "{:#?}" prints
+// every element on its own line, keyed by the index of its first bit
+fn fmt_debug<F: Fn(usize) -> bool>(f: &mut fmt::Formatter, name: &str, capacity: usize, word_size: usize, get: F) -> fmt::Result {
+    let word_count = capacity.div_ceil(word_size);
+    let word_bits = |word_index: usize| {
+        let start = word_index * word_size;
+        to_binary_string((start..cmp::min(start + word_size, capacity)).map(&get))
+    };
+
+    if f.alternate() {
+        let mut debug_struct = f.debug_struct(name);
+        for word_index in 0..word_count {
+            debug_struct.field(&(word_index * word_size).to_string(), &DebugBits(&word_bits(word_index)));
+        }
+        return debug_struct.field("len", &capacity).finish();
+    }
+
+    f.write_str("[")?;
+    let mut word_index = 0;
+    while word_index < word_count {
+        if word_count > DEBUG_MAX_WORDS && word_index == DEBUG_MAX_WORDS / 2 {
+            f.write_str("... ")?;
+            word_index = word_count - DEBUG_MAX_WORDS / 2;
+        }
+        write!(f, "{} ", word_bits(word_index))?;
+        word_index += 1;
+    }
+    write!(f, "| len={}]", capacity)
+}
+
+struct DebugBits<'a>(&'a str);
+
+impl<'a> fmt::Debug for DebugBits<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+fn to_binary_string<I: Iterator<Item = bool>>(bits: I) -> String {
+    bits.map(|bit| if bit { '1' } else { '0' }).collect()
+}
+
+// a trailing partial digit is padded with zero bits
+fn to_hex_string<I: Iterator<Item = bool>>(bits: I, uppercase: bool) -> String {
+    let mut string = String::new();
+    let mut digit = 0;
+    let mut bit_count = 0;
+    for bit in bits {
+        digit = (digit << 1) | bit as u32;
+        bit_count += 1;
+        if bit_count == 4 {
+            string.push(to_hex_digit(digit, uppercase));
+            digit = 0;
+            bit_count = 0;
+        }
+    }
+    if bit_count > 0 {
+        string.push(to_hex_digit(digit << (4 - bit_count), uppercase));
+    }
+    string
+}
+
+#[inline]
+fn to_hex_digit(digit: u32, uppercase: bool) -> char {
+    let digit = char::from_digit(digit, 16).unwrap();
+    if uppercase { digit.to_ascii_uppercase() } else { digit }
+}
+
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum ParseBitVectorError {
+    InvalidDigit { digit: char, position: usize }
+}
+
+impl fmt::Display for ParseBitVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseBitVectorError::InvalidDigit { digit, position } => write!(f, "Invalid digit. Digit = {:?}, Position = {}", digit, position)
+        }
+    }
+}
+
+impl Error for ParseBitVectorError {}
+
+/// Parses plain binary such as `"10110010"`, binary with a prefix such as `"0b1011_0010"` or hex such as `"0xB2"`.
+/// Underscores are ignored.
+impl<S: BitStorage, O: BitOrder> FromStr for BitVector<S, O> {
+    type Err = ParseBitVectorError;
+
+    fn from_str(string: &str) -> Result<BitVector<S, O>, ParseBitVectorError> {
+        let (digits, is_hex, prefix_len) = if let Some(digits) = string.strip_prefix("0x") {
+            (digits, true, 2)
+        }
+        else if let Some(digits) = string.strip_prefix("0b") {
+            (digits, false, 2)
+        }
+        else {
+            (string, false, 0)
+        };
+
+        let mut bit_vector = BitVector::with_capacity(0, false);
+        for (index, digit) in digits.chars().enumerate() {
+            if digit == '_' {
+                continue;
+            }
+            let invalid_digit = ParseBitVectorError::InvalidDigit { digit, position: prefix_len + index };
+            if is_hex {
+                let value = digit.to_digit(16).ok_or(invalid_digit)?;
+                for shift in (0..4).rev() {
+                    bit_vector.push((value >> shift) & 1 == 1);
+                }
+            }
+            else {
+                match digit {
+                    '0' => bit_vector.push(false),
+                    '1' => bit_vector.push(true),
+                    _ => return Err(invalid_digit)
+                }
+            }
+        }
+        Ok(bit_vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BitArray,BitSlice,BitSliceMut,BitVector,Msb0,SmallBitVector};
+    use super::ParseBitVectorError;
+
+    fn create_bitvector_u8_14() -> BitVector<u8> {
+        let mut vec = BitVector::with_capacity(14, false);
+        vec.set(0, true);
+        vec.set(2, true);
+        vec.set(3, true);
+        vec.set(6, true);
+        vec.set(9, true);
+        vec
+    }
+
+    #[test]
+    fn test_display() {
+        let vec = create_bitvector_u8_14();
+        assert_eq!(format!("{}", vec), "10110010010000");
+        assert_eq!(format!("{:>16}", vec), "  10110010010000");
+        assert_eq!(format!("{}", BitVector::<u8>::with_capacity(0, false)), "");
+    }
+
+    #[test]
+    fn test_binary() {
+        let vec = create_bitvector_u8_14();
+        assert_eq!(format!("{:b}", vec), "10110010010000");
+        assert_eq!(format!("{:#b}", vec), "0b10110010010000");
+        assert_eq!(format!("{:#018b}", vec), "0b0010110010010000");
+    }
+
+    #[test]
+    fn test_hex() {
+        let vec = create_bitvector_u8_14();
+        assert_eq!(format!("{:x}", vec), "b240");
+        assert_eq!(format!("{:#X}", vec), "0xB240");
+
+        let vec: BitVector<u32> = "0xdeadbeef".parse().unwrap();
+        assert_eq!(format!("{:x}", vec), "deadbeef");
+    }
+
+    #[test]
+    fn test_slices() {
+        let data: [u8; 2] = [0b01001101, 0b00000010];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 14);
+        assert_eq!(format!("{}", slice), "10110010010000");
+        assert_eq!(format!("{:#x}", slice), "0xb240");
+
+        let mut data: [u8; 2] = [0b10110010, 0b01000000];
+        let slice: BitSliceMut<u8, Msb0> = BitSliceMut::from_slice_mut_with_len(&mut data, 14);
+        assert_eq!(format!("{:b}", slice), "10110010010000");
+        assert_eq!(format!("{:X}", slice), "B240");
+    }
+
+    #[test]
+    fn test_debug() {
+        let vec = create_bitvector_u8_14();
+        assert_eq!(format!("{:?}", vec), "[10110010 010000 | len=14]");
+        assert_eq!(format!("{:?}", BitVector::<u8>::with_capacity(0, false)), "[| len=0]");
+
+        let vec = BitVector::<u32, Msb0>::from_bytes(&[0b10110010, 0, 0, 0b10000000]);
+        assert_eq!(format!("{:?}", vec), "[10110010000000000000000010000000 | len=32]");
+
+        let vec = BitVector::<u8>::with_capacity(100, true);
+        assert_eq!(format!("{:?}", vec), "[11111111 11111111 11111111 11111111 ... 11111111 11111111 11111111 1111 | len=100]");
+    }
+
+    #[test]
+    fn test_debug_alternate() {
+        let vec = create_bitvector_u8_14();
+        assert_eq!(format!("{:#?}", vec), "BitVector {\n    0: 10110010,\n    8: 010000,\n    len: 14,\n}");
+
+        let (_, right) = vec.split_at(8);
+        assert_eq!(format!("{:#?}", right), "BitSlice {\n    0: 010000,\n    len: 6,\n}");
+    }
+
+    #[test]
+    fn test_debug_slices() {
+        let data: [u8; 2] = [0b01001101, 0b11111110];
+        let slice: BitSlice<u8> = BitSlice::from_slice_with_len(&data, 14);
+        assert_eq!(format!("{:?}", slice), "[10110010 011111 | len=14]");
+
+        let mut data: [u8; 2] = [0b10110010, 0b01000000];
+        let slice: BitSliceMut<u8, Msb0> = BitSliceMut::from_slice_mut_with_len(&mut data, 12);
+        assert_eq!(format!("{:?}", slice), "[10110010 0100 | len=12]");
+    }
+
+    #[test]
+    fn test_debug_bit_array() {
+        let mut array = BitArray::<u8, 12, 2>::new(false);
+        array.set(0, true);
+        array.set(11, true);
+        assert_eq!(format!("{:?}", array), "[10000000 0001 | len=12]");
+        assert_eq!(format!("{}", array), "100000000001");
+    }
+
+    #[test]
+    fn test_debug_small_bit_vector() {
+        let mut vec = SmallBitVector::<u8, 2>::with_capacity(12, false);
+        vec.set(0, true);
+        assert_eq!(format!("{:?}", vec), "[10000000 0000 | len=12]");
+
+        vec.resize(20, true);
+        assert_eq!(format!("{:?}", vec), "[10000000 00001111 1111 | len=20]");
+        assert_eq!(format!("{}", vec), "10000000000011111111");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let expected = create_bitvector_u8_14();
+
+        let vec: BitVector<u8> = "10110010010000".parse().unwrap();
+        assert_eq!(vec.as_words(), expected.as_words());
+        assert_eq!(vec.capacity(), 14);
+
+        let vec: BitVector<u8> = "0b1011_0010_0100_00".parse().unwrap();
+        assert_eq!(vec.as_words(), expected.as_words());
+        assert_eq!(vec.capacity(), 14);
+
+        let vec: BitVector<u8> = "0xB2".parse().unwrap();
+        assert_eq!(vec.as_words(), [0b01001101]);
+        assert_eq!(vec.capacity(), 8);
+
+        let vec: BitVector<u16, Msb0> = "0xb2_4".parse().unwrap();
+        assert_eq!(vec.as_words(), [0b1011001001000000]);
+        assert_eq!(vec.capacity(), 12);
+
+        let vec: BitVector<u8> = "".parse().unwrap();
+        assert_eq!(vec.capacity(), 0);
+    }
+
+    #[test]
+    fn test_from_str_round_trip() {
+        let vec = create_bitvector_u8_14();
+        let parsed: BitVector<u8> = vec.to_string().parse().unwrap();
+        assert_eq!(parsed.as_words(), vec.as_words());
+        assert_eq!(parsed.capacity(), vec.capacity());
+    }
+
+    #[test]
+    fn test_from_str_invalid_digit() {
+        assert_eq!("1012".parse::<BitVector<u8>>().unwrap_err(), ParseBitVectorError::InvalidDigit { digit: '2', position: 3 });
+        assert_eq!("0b10_x".parse::<BitVector<u8>>().unwrap_err(), ParseBitVectorError::InvalidDigit { digit: 'x', position: 5 });
+        assert_eq!("0xbg".parse::<BitVector<u8>>().unwrap_err(), ParseBitVectorError::InvalidDigit { digit: 'g', position: 3 });
+        assert_eq!("0X12".parse::<BitVector<u8>>().unwrap_err(), ParseBitVectorError::InvalidDigit { digit: 'X', position: 1 });
+    }
+}
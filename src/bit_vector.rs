@@ -1,30 +1,85 @@
-use num;
-
+use std::fmt;
+use std::marker::PhantomData;
 use std::ops::Index;
 
 use BitStorage;
+use BitOrder;
+use Lsb0;
+use BitArray;
 use BitSlice;
 use BitSliceMut;
+use bit_slice;
+use bit_slice::{IterOnes,IterRuns};
 
 use TRUE;
 use FALSE;
 
 //TODO wait on custom DST on stable and deref BitVector into BitSlice resp BitSliceMut and implement non-structural changing methods on BitSlice/BitSliceMut
 
-#[derive(Debug)]
-pub struct BitVector<S: BitStorage> {
+#[derive(Clone)]
+pub struct BitVector<S: BitStorage, O: BitOrder = Lsb0> {
     data: Vec<S>,
-    capacity: usize
+    capacity: usize,
+    order: PhantomData<O>
 }
 
-impl<S: BitStorage> BitVector<S> {
-    pub fn with_capacity(capacity: usize, default: bool) -> BitVector<S> {
-        let len = (capacity / S::storage_size()) + 1;
-        let default = if default { S::max_value() } else { S::zero() };
-        BitVector { 
+impl<S: BitStorage, O: BitOrder> BitVector<S, O> {
+    pub fn with_capacity(capacity: usize, default: bool) -> BitVector<S, O> {
+        let len = capacity.div_ceil(S::storage_size());
+        let default = if default { S::MAX } else { S::ZERO };
+        let mut bit_vector = BitVector {
             data: vec![default; len],
-            capacity: capacity
+            capacity,
+            order: PhantomData
+        };
+        bit_vector.clear_unused_bits();
+        bit_vector
+    }
+
+    pub fn from_vec(data: Vec<S>, capacity: usize) -> BitVector<S, O> {
+        if capacity > data.len() * S::storage_size() {
+            panic!("Capacity exceeds storage. Maximum capacity = {}, Capacity = {}", data.len() * S::storage_size(), capacity);
         }
+        let mut bit_vector = BitVector {
+            data,
+            capacity,
+            order: PhantomData
+        };
+        bit_vector.data.truncate(capacity.div_ceil(S::storage_size()));
+        bit_vector.clear_unused_bits();
+        bit_vector
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> BitVector<S, O> {
+        let bytes_per_element = S::storage_size() / 8;
+        let data = bytes.chunks(bytes_per_element).map(|chunk| {
+            chunk.iter().enumerate().fold(S::ZERO, |element, (byte_index, &byte)| {
+                element | (S::from_u64(byte as u64) << O::byte_shift::<S>(byte_index))
+            })
+        }).collect();
+        BitVector::from_vec(data, bytes.len() * 8)
+    }
+
+    pub fn into_vec(self) -> Vec<S> {
+        self.data
+    }
+
+    pub fn as_words(&self) -> &[S] {
+        &self.data
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bytes_per_element = S::storage_size() / 8;
+        let (last_index, remainder) = S::compute_data_index_and_remainder(self.capacity);
+        let mut bytes: Vec<u8> = self.data.iter().enumerate().flat_map(|(data_index, &element)| {
+            // the padding bits can be set through `as_raw_mut_slice`
+            let element = if data_index == last_index { element & O::first_bits_mask(remainder) } else { element };
+            (0..bytes_per_element).map(move |byte_index| {
+                (element >> O::byte_shift::<S>(byte_index)).to_u64() as u8
+            })
+        }).collect();
+        bytes.truncate(self.capacity.div_ceil(8));
+        bytes
     }
 
     pub fn get(&self, index: usize) -> Option<bool> {
@@ -37,14 +92,82 @@ impl<S: BitStorage> BitVector<S> {
     pub fn set(&mut self, index: usize, value: bool) {
         self.panic_index_bounds(index);
         let (data_index, remainder) = S::compute_data_index_and_remainder(index);
-        S::set(&mut self.data[data_index], remainder, value);
+        S::set(&mut self.data[data_index], O::storage_index::<S>(remainder), value);
+    }
+
+    /// Returns the `len` bits starting at `start`, at most 64. The first bit is the least significant bit of the
+    /// value for `Lsb0` and the most significant bit for `Msb0`.
+    pub fn get_bits(&self, start: usize, len: usize) -> u64 {
+        bit_slice::panic_bits_out_of_bounds(self.capacity, start, len);
+        bit_slice::get_bits::<S, O>(&self.data, start, len)
+    }
+
+    /// Replaces the `len` bits starting at `start` with `value`, the inverse of `get_bits`.
+    pub fn set_bits(&mut self, start: usize, len: usize, value: u64) {
+        bit_slice::panic_bits_out_of_bounds(self.capacity, start, len);
+        bit_slice::panic_value_exceeds_bits(len, value);
+        bit_slice::set_bits::<S, O>(&mut self.data, start, len, value);
     }
 
     pub fn capacity(&self) -> usize {
         self.capacity
     }
 
-    pub fn split_at(&self, index: usize) -> (BitSlice<S>, BitSlice<S>) {
+    pub fn resize(&mut self, new_capacity: usize, value: bool) {
+        if value && new_capacity > self.capacity {
+            let (data_index, remainder) = S::compute_data_index_and_remainder(self.capacity);
+            if data_index < self.data.len() {
+                self.data[data_index] |= !O::first_bits_mask::<S>(remainder);
+            }
+        }
+        let default = if value { S::MAX } else { S::ZERO };
+        self.data.resize(new_capacity.div_ceil(S::storage_size()), default);
+        self.capacity = new_capacity;
+        self.clear_unused_bits();
+    }
+
+    pub fn push(&mut self, value: bool) {
+        let capacity = self.capacity;
+        self.resize(capacity + 1, value);
+    }
+
+    pub fn fill(&mut self, value: bool) {
+        let element = if value { S::MAX } else { S::ZERO };
+        self.data.fill(element);
+        self.clear_unused_bits();
+    }
+
+    /// Keeps the bits that are also set in `other`, which must have the same length.
+    pub fn and(&mut self, other: &BitVector<S, O>) {
+        self.combine(other, |left, right| left & right);
+    }
+
+    pub fn or(&mut self, other: &BitVector<S, O>) {
+        self.combine(other, |left, right| left | right);
+    }
+
+    pub fn xor(&mut self, other: &BitVector<S, O>) {
+        self.combine(other, |left, right| left ^ right);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.as_bitslice().count_ones()
+    }
+
+    /// Returns the number of set bits before `index`.
+    pub fn rank(&self, index: usize) -> usize {
+        self.as_bitslice().rank(index)
+    }
+
+    pub fn iter_ones(&self) -> IterOnes<'_, S, O> {
+        self.as_bitslice().iter_ones()
+    }
+
+    pub fn iter_runs(&self) -> IterRuns<'_, S, O> {
+        self.as_bitslice().iter_runs()
+    }
+
+    pub fn split_at(&self, index: usize) -> (BitSlice<'_, S, O>, BitSlice<'_, S, O>) {
         self.panic_index_not_on_storage_bound(index);
         let data_index = S::compute_data_index(index);
         let (capacity_left, capacity_right) = self.compute_capacities(index);
@@ -57,7 +180,7 @@ impl<S: BitStorage> BitVector<S> {
         }
     }
 
-    pub fn split_at_mut(&mut self, index: usize) -> (BitSliceMut<S>, BitSliceMut<S>) {
+    pub fn split_at_mut(&mut self, index: usize) -> (BitSliceMut<'_, S, O>, BitSliceMut<'_, S, O>) {
         self.panic_index_not_on_storage_bound(index);
         let data_index = S::compute_data_index(index);
         let (capacity_left, capacity_right) = self.compute_capacities(index);
@@ -70,12 +193,21 @@ impl<S: BitStorage> BitVector<S> {
         }
     }
 
-    pub fn iter(&self) -> Iter<S> {
+    pub fn as_bitslice(&self) -> BitSlice<'_, S, O> {
+        unsafe { BitSlice::from_pointer(self.data.as_ptr(), self.capacity) }
+    }
+
+    pub fn as_bitslice_mut(&mut self) -> BitSliceMut<'_, S, O> {
+        unsafe { BitSliceMut::from_pointer(self.data.as_mut_ptr(), self.capacity) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, S, O> {
         Iter {
             data: &self.data,
             capacity: self.capacity,
             data_index_counter: 0,
-            remainder_counter: 0
+            remainder_counter: 0,
+            order: PhantomData
         }
     }
 
@@ -86,8 +218,19 @@ impl<S: BitStorage> BitVector<S> {
     }
 
     #[inline]
-    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: S) -> bool {
-        S::get(&self.data[data_index], remainder)
+    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: u32) -> bool {
+        S::get(&self.data[data_index], O::storage_index::<S>(remainder))
+    }
+
+    // works on whole elements, which is a vector operation for wide storage types
+    #[inline]
+    fn combine<F: Fn(S, S) -> S>(&mut self, other: &BitVector<S, O>, operation: F) {
+        if self.capacity != other.capacity {
+            panic!("Lengths do not match. Length = {}, Other length = {}", self.capacity, other.capacity);
+        }
+        for (element, &other_element) in self.data.iter_mut().zip(&other.data) {
+            *element = operation(*element, other_element);
+        }
     }
 
     #[inline]
@@ -95,6 +238,16 @@ impl<S: BitStorage> BitVector<S> {
         (index_to_split, self.capacity - index_to_split)
     }
 
+    // bits past the capacity are kept at zero, so whole elements can be compared and counted
+    #[inline]
+    fn clear_unused_bits(&mut self) {
+        let (data_index, remainder) = S::compute_data_index_and_remainder(self.capacity);
+        if data_index < self.data.len() {
+            self.data[data_index] &= O::first_bits_mask(remainder);
+        }
+    }
+
+
     #[inline]
     fn index_in_bounds(&self, index: usize) -> bool {
         index < self.capacity
@@ -115,7 +268,13 @@ impl<S: BitStorage> BitVector<S> {
     }
 }
 
-impl<S: BitStorage> Index<usize> for BitVector<S> {
+impl<S: BitStorage, O: BitOrder, const N: usize, const W: usize> From<BitArray<S, N, W, O>> for BitVector<S, O> {
+    fn from(bit_array: BitArray<S, N, W, O>) -> BitVector<S, O> {
+        BitVector::from_vec(bit_array.as_words().to_vec(), N)
+    }
+}
+
+impl<S: BitStorage, O: BitOrder> Index<usize> for BitVector<S, O> {
     type Output = bool;
 
     fn index(&self, index: usize) -> &bool {
@@ -124,34 +283,40 @@ impl<S: BitStorage> Index<usize> for BitVector<S> {
     }
 }
 
-impl<'a, S: BitStorage + 'a> IntoIterator for &'a BitVector<S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> IntoIterator for &'a BitVector<S, O> {
     type Item = bool;
-    type IntoIter = Iter<'a, S>;
+    type IntoIter = Iter<'a, S, O>;
 
-    fn into_iter(self) -> Iter<'a, S> {
+    fn into_iter(self) -> Iter<'a, S, O> {
         self.iter()
     }
 }
 
-#[derive(Debug)]
-pub struct Iter<'a, S: BitStorage + 'a> {
+pub struct Iter<'a, S: BitStorage + 'a, O: BitOrder = Lsb0> {
     data: &'a Vec<S>,
     capacity: usize,
     data_index_counter: usize,
-    remainder_counter: usize
+    remainder_counter: usize,
+    order: PhantomData<O>
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> fmt::Debug for Iter<'a, S, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Iter").field("index", &self.calculate_index()).field("len", &self.capacity).finish()
+    }
 }
 
-impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iterator for Iter<'a, S, O> {
     type Item = bool;
 
     fn next(&mut self) -> Option<bool> {
-        let remainder: S = num::cast(self.remainder_counter).unwrap();
-        let next = self.get_unchecked_by_data_index_and_remainder(self.data_index_counter, remainder);
-
         if self.calculate_index() == self.capacity {
             return None;
         }
 
+        let remainder = self.remainder_counter as u32;
+        let next = self.get_unchecked_by_data_index_and_remainder(self.data_index_counter, remainder);
+
         self.remainder_counter += 1;
         if self.remainder_counter == S::storage_size() {
             self.remainder_counter = 0;
@@ -162,10 +327,10 @@ impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
     }
 }
 
-impl<'a, S: BitStorage + 'a> Iter<'a, S> {
+impl<'a, S: BitStorage + 'a, O: BitOrder> Iter<'a, S, O> {
     #[inline]
-    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: S) -> bool {
-        S::get(&self.data[data_index], remainder)
+    fn get_unchecked_by_data_index_and_remainder(&self, data_index: usize, remainder: u32) -> bool {
+        S::get(&self.data[data_index], O::storage_index::<S>(remainder))
     }
 
     #[inline]
@@ -176,6 +341,7 @@ impl<'a, S: BitStorage + 'a> Iter<'a, S> {
 
 #[cfg(test)]
 mod tests {
+    use super::super::{BitArray,Msb0};
     use super::BitVector;
 
     #[test]
@@ -199,6 +365,88 @@ mod tests {
         assert!(vec_32_1000_true.iter().all(|x| x));
     }
 
+    #[test]
+    fn test_with_capacity_clears_unused_bits() {
+        let vec = BitVector::<u8>::with_capacity(12, true);
+        assert_eq!(vec.as_words(), [0b11111111, 0b00001111]);
+
+        let vec = BitVector::<u8>::with_capacity(16, true);
+        assert_eq!(vec.as_words(), [0b11111111, 0b11111111]);
+
+        let vec = BitVector::<u8>::with_capacity(0, true);
+        assert!(vec.as_words().is_empty());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let vec = BitVector::<u16>::from_vec(vec![0b1000000000000001, 0b0000000000000001], 17);
+        assert_eq!(vec.capacity(), 17);
+        assert_eq!(vec[0], true);
+        assert_eq!(vec[15], true);
+        assert_eq!(vec[16], true);
+        assert_eq!(vec.count_ones(), 3);
+    }
+
+    #[test]
+    fn test_from_vec_clears_unused_bits() {
+        let vec = BitVector::<u8>::from_vec(vec![0b11111111, 0b11111111, 0b11111111], 10);
+        assert_eq!(vec.as_words(), [0b11111111, 0b00000011]);
+
+        let vec = BitVector::<u8, Msb0>::from_vec(vec![0b11111111, 0b11111111], 10);
+        assert_eq!(vec.as_words(), [0b11111111, 0b11000000]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_vec_exceeding_storage() {
+        BitVector::<u8>::from_vec(vec![0, 0], 17);
+    }
+
+    #[test]
+    fn test_into_vec() {
+        let data = vec![0b10110010u8, 0b00000001];
+        let pointer = data.as_ptr();
+
+        let vec = BitVector::<u8>::from_vec(data, 9);
+        let data = vec.into_vec();
+        assert_eq!(data, [0b10110010, 0b00000001]);
+        assert_eq!(data.as_ptr(), pointer);
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let bytes = [0b10110010, 0b00000001, 0b11110000];
+
+        let vec = BitVector::<u8>::from_bytes(&bytes);
+        assert_eq!(vec.capacity(), 24);
+        assert_eq!(vec.as_words(), bytes);
+
+        let vec = BitVector::<u16>::from_bytes(&bytes);
+        assert_eq!(vec.as_words(), [0b0000000110110010, 0b0000000011110000]);
+        assert_eq!(vec[1], true);
+        assert_eq!(vec[8], true);
+
+        let vec = BitVector::<u16, Msb0>::from_bytes(&bytes);
+        assert_eq!(vec.as_words(), [0b1011001000000001, 0b1111000000000000]);
+        assert_eq!(vec[0], true);
+        assert_eq!(vec[15], true);
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let bytes = [0b10110010, 0b00000001, 0b11110000];
+
+        assert_eq!(BitVector::<u8>::from_bytes(&bytes).to_bytes(), bytes);
+        assert_eq!(BitVector::<u32>::from_bytes(&bytes).to_bytes(), bytes);
+        assert_eq!(BitVector::<u32, Msb0>::from_bytes(&bytes).to_bytes(), bytes);
+        assert_eq!(BitVector::<u64, Msb0>::from_bytes(&bytes).to_bytes(), bytes);
+
+        let mut vec = BitVector::<u32, Msb0>::with_capacity(10, false);
+        vec.set(0, true);
+        vec.set(9, true);
+        assert_eq!(vec.to_bytes(), [0b10000000, 0b01000000]);
+    }
+
     #[test]
     fn test_get_set() {
         let mut vec = BitVector::<u8>::with_capacity(16, false);
@@ -238,6 +486,36 @@ mod tests {
         assert_eq!(vec.get(15).unwrap(), true);
     }
 
+    #[test]
+    fn test_get_set_msb0() {
+        let mut vec = BitVector::<u8, Msb0>::with_capacity(12, false);
+
+        vec.set(0, true);
+        vec.set(3, true);
+        vec.set(8, true);
+        vec.set(11, true);
+
+        assert_eq!(vec.as_words(), [0b10010000, 0b10010000]);
+        assert_eq!(vec[0], true);
+        assert_eq!(vec[1], false);
+        assert_eq!(vec[11], true);
+    }
+
+    #[test]
+    fn test_with_capacity_and_resize_msb0() {
+        let mut vec = BitVector::<u8, Msb0>::with_capacity(12, true);
+        assert_eq!(vec.as_words(), [0b11111111, 0b11110000]);
+
+        vec.resize(14, false);
+        assert_eq!(vec.as_words(), [0b11111111, 0b11110000]);
+
+        vec.resize(16, true);
+        assert_eq!(vec.as_words(), [0b11111111, 0b11110011]);
+
+        vec.resize(3, false);
+        assert_eq!(vec.as_words(), [0b11100000]);
+    }
+
     #[test]
     fn test_repeated_set() {
         let mut vec = BitVector::<u8>::with_capacity(16, false);
@@ -326,7 +604,7 @@ mod tests {
     fn test_index_out_of_bounds() {
         let vec = BitVector::<u8>::with_capacity(16, false);
 
-        vec[16];
+        let _ = vec[16];
     }
 
     #[test]
@@ -338,6 +616,215 @@ mod tests {
         assert_eq!(vec_1024.capacity(), 1024);
     }
 
+    #[test]
+    fn test_resize() {
+        let mut vec = BitVector::<u8>::with_capacity(4, false);
+        vec.set(1, true);
+
+        vec.resize(20, true);
+        assert_eq!(vec.capacity(), 20);
+        let vec_iter_vec: Vec<_> = vec.iter().collect();
+        assert_eq!(vec_iter_vec, [false, true, false, false, true, true, true, true, true, true, true, true, true, true, true, true, true, true, true, true]);
+
+        vec.resize(6, false);
+        assert_eq!(vec.capacity(), 6);
+        assert_eq!(vec.as_words(), [0b00110010]);
+
+        vec.resize(10, false);
+        let vec_iter_vec: Vec<_> = vec.iter().collect();
+        assert_eq!(vec_iter_vec, [false, true, false, false, true, true, false, false, false, false]);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut vec = BitVector::<u8>::with_capacity(0, false);
+
+        for i in 0..20 {
+            vec.push(i % 3 == 0);
+        }
+
+        assert_eq!(vec.capacity(), 20);
+        assert_eq!(vec.count_ones(), 7);
+        assert_eq!(vec[18], true);
+        assert_eq!(vec[19], false);
+    }
+
+    #[test]
+    fn test_as_bitslice() {
+        let mut vec = BitVector::<u8>::with_capacity(12, false);
+        vec.as_bitslice_mut().set(10, true);
+
+        let slice = vec.as_bitslice();
+        assert_eq!(slice.capacity(), 12);
+        assert_eq!(slice[10], true);
+        assert_eq!(slice[9], false);
+    }
+
+    #[test]
+    fn test_from_bit_array() {
+        let mut array = BitArray::<u8, 16, 2>::new(false);
+        array.set(3, true);
+        array.set(15, true);
+
+        let vec = BitVector::from(array);
+        assert_eq!(vec.capacity(), 16);
+        assert_eq!(vec.count_ones(), 2);
+        assert_eq!(vec[3], true);
+        assert_eq!(vec[15], true);
+    }
+
+    #[test]
+    fn test_count_ones() {
+        let mut vec = BitVector::<u32>::with_capacity(100, false);
+        assert_eq!(vec.count_ones(), 0);
+
+        vec.set(0, true);
+        vec.set(31, true);
+        vec.set(32, true);
+        vec.set(99, true);
+        assert_eq!(vec.count_ones(), 4);
+
+        let vec = BitVector::<u32>::with_capacity(100, true);
+        assert_eq!(vec.count_ones(), 100);
+    }
+
+    #[test]
+    fn test_padding_set_through_raw_slice() {
+        let mut vec = BitVector::<u8>::with_capacity(4, false);
+        vec.as_bitslice_mut().as_raw_mut_slice()[0] = 0b11111111;
+
+        assert_eq!(vec.count_ones(), 4);
+        assert_eq!(vec.as_bitslice().count_ones(), 4);
+        assert_eq!(vec.to_bytes(), [0b00001111]);
+
+        let mut vec = BitVector::<u16, Msb0>::with_capacity(12, false);
+        vec.as_bitslice_mut().as_raw_mut_slice()[0] = 0b11111111_11111111;
+        assert_eq!(vec.count_ones(), 12);
+        assert_eq!(vec.to_bytes(), [0b11111111, 0b11110000]);
+    }
+
+    #[test]
+    fn test_rank_and_iter_ones() {
+        let mut vec = BitVector::<u32>::with_capacity(100, false);
+        vec.set(0, true);
+        vec.set(31, true);
+        vec.set(32, true);
+        vec.set(99, true);
+
+        assert_eq!(vec.rank(0), 0);
+        assert_eq!(vec.rank(32), 2);
+        assert_eq!(vec.rank(99), 3);
+        assert_eq!(vec.rank(100), 4);
+
+        let ones: Vec<_> = vec.iter_ones().collect();
+        assert_eq!(ones, [0, 31, 32, 99]);
+    }
+
+    #[test]
+    fn test_fill() {
+        let mut vec = BitVector::<u32>::with_capacity(40, false);
+        vec.fill(true);
+        assert_eq!(vec.count_ones(), 40);
+        assert_eq!(vec.as_words(), [0xffffffff, 0x000000ff]);
+
+        vec.fill(false);
+        assert_eq!(vec.count_ones(), 0);
+    }
+
+    #[test]
+    fn test_and_or_xor() {
+        let left: BitVector<u8> = "1100110011".parse().unwrap();
+        let right: BitVector<u8> = "1010101010".parse().unwrap();
+
+        let mut and = left.clone();
+        and.and(&right);
+        assert_eq!(and.to_string(), "1000100010");
+
+        let mut or = left.clone();
+        or.or(&right);
+        assert_eq!(or.to_string(), "1110111011");
+
+        let mut xor = left.clone();
+        xor.xor(&right);
+        assert_eq!(xor.to_string(), "0110011001");
+    }
+
+    #[test]
+    #[should_panic(expected = "Lengths do not match. Length = 10, Other length = 9")]
+    fn test_and_lengths_do_not_match() {
+        let mut vec = BitVector::<u8>::with_capacity(10, false);
+        vec.and(&BitVector::with_capacity(9, false));
+    }
+
+    #[test]
+    fn test_u128() {
+        let mut vec = BitVector::<u128>::with_capacity(300, false);
+        vec.set(0, true);
+        vec.set(127, true);
+        vec.set(128, true);
+        vec.set(299, true);
+        assert_eq!(vec.as_words(), [1 | 1 << 127, 1, 1 << 43]);
+        assert_eq!(vec.count_ones(), 4);
+        assert_eq!(vec.rank(128), 2);
+        assert_eq!(vec.iter_ones().collect::<Vec<_>>(), [0, 127, 128, 299]);
+        assert_eq!(vec.get_bits(120, 16), 0b00000001_10000000);
+
+        vec.set_bits(200, 64, u64::MAX);
+        assert_eq!(vec.count_ones(), 68);
+        assert_eq!(vec.to_bytes()[16], 1);
+
+        let mut bytes = Vec::new();
+        vec.write_to(&mut bytes).unwrap();
+        let read = BitVector::<u128>::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.as_words(), vec.as_words());
+
+        let mut msb0 = BitVector::<u128, Msb0>::with_capacity(130, false);
+        msb0.set(1, true);
+        msb0.set(129, true);
+        assert_eq!(msb0.as_words(), [1 << 126, 1 << 126]);
+        assert_eq!(msb0.iter_ones().collect::<Vec<_>>(), [1, 129]);
+    }
+
+    #[test]
+    fn test_get_and_set_bits() {
+        let mut vec = BitVector::<u8>::with_capacity(40, false);
+        vec.set_bits(5, 13, 0x1abc);
+        assert_eq!(vec.as_words(), [0b10000000, 0b01010111, 0b00000011, 0b00000000, 0b00000000]);
+        assert_eq!(vec.get_bits(5, 13), 0x1abc);
+        assert_eq!(vec.get_bits(0, 0), 0);
+
+        vec.set_bits(8, 8, 0);
+        assert_eq!(vec.get_bits(5, 13), 0x1804);
+
+        let mut vec = BitVector::<u32>::with_capacity(100, true);
+        vec.set_bits(20, 64, 0x0123_4567_89ab_cdef);
+        assert_eq!(vec.get_bits(20, 64), 0x0123_4567_89ab_cdef);
+        assert_eq!(vec.get_bits(16, 8), 0xff);
+        assert_eq!(vec.count_ones(), 36 + 0x0123_4567_89ab_cdefu64.count_ones() as usize);
+    }
+
+    #[test]
+    fn test_get_and_set_bits_msb0() {
+        let mut vec = BitVector::<u16, Msb0>::with_capacity(32, false);
+        vec.set_bits(5, 13, 0x1abc);
+        assert_eq!(vec.to_string(), "00000110101011110000000000000000");
+        assert_eq!(vec.as_words(), [0x06af, 0x0000]);
+        assert_eq!(vec.get_bits(5, 13), 0x1abc);
+        assert_eq!(vec.get_bits(0, 16), 0x06af);
+    }
+
+    #[test]
+    #[should_panic(expected = "Range out of bounds. Length = 40, Start = 30, Bit count = 11")]
+    fn test_get_bits_out_of_bounds() {
+        BitVector::<u8>::with_capacity(40, false).get_bits(30, 11);
+    }
+
+    #[test]
+    #[should_panic(expected = "Value exceeds bit count. Bit count = 3, Value = 8")]
+    fn test_set_bits_value_exceeds_bit_count() {
+        BitVector::<u8>::with_capacity(40, false).set_bits(0, 3, 8);
+    }
+
     #[test]
     fn test_split_at() {
         let mut vec = BitVector::<u8>::with_capacity(16, false);
@@ -540,12 +1027,16 @@ mod tests {
     #[test]
     fn test_derive_debug() {
         let vec: BitVector<u32> = BitVector::with_capacity(32, true);
-        format!("{:?}", vec);
+        let _ = format!("{:?}", vec);
     }
 
     #[test]
     fn test_derive_iter_debug() {
         let vec: BitVector<u32> = BitVector::with_capacity(32, true);
-        format!("{:?}", vec.iter());
+        let _ = format!("{:?}", vec.iter());
+
+        let mut iter = vec.iter();
+        iter.next();
+        assert_eq!(format!("{:?}", iter), "Iter { index: 1, len: 32 }");
     }
 }
\ No newline at end of file
@@ -0,0 +1,138 @@
+use std::hash::Hash;
+
+use bloom_filter::{hash_pair,optimal_parameters};
+use BitVector;
+
+const BLOCK_BITS: usize = 512;
+const BLOCK_WORDS: usize = BLOCK_BITS / 64;
+
+/// A Bloom filter that confines the bits of every item to a single 512 bit block.
+///
+/// The first hash picks the block and the second one drives the probes inside it, so a lookup reads 8 consecutive
+/// words instead of `hash_count` scattered ones. Blocks are only aligned to their words, so a block can span two cache
+/// lines. The price is a somewhat higher false positive rate than a `BloomFilter` of the same size.
+#[derive(Clone,Debug)]
+pub struct BlockedBloomFilter {
+    bits: BitVector<u64>,
+    hash_count: u32
+}
+
+impl BlockedBloomFilter {
+    pub fn new(block_count: usize, hash_count: u32) -> BlockedBloomFilter {
+        assert!(block_count > 0, "Bloom filter needs at least one block.");
+        assert!(hash_count > 0, "Bloom filter needs at least one hash function.");
+        BlockedBloomFilter {
+            bits: BitVector::with_capacity(block_count * BLOCK_BITS, false),
+            hash_count
+        }
+    }
+
+    /// Sizes the filter like `BloomFilter::with_false_positive_rate` and rounds up to whole blocks.
+    pub fn with_false_positive_rate(item_count: usize, false_positive_rate: f64) -> BlockedBloomFilter {
+        let (bit_count, hash_count) = optimal_parameters(item_count, false_positive_rate);
+        BlockedBloomFilter::new(bit_count.div_ceil(BLOCK_BITS), hash_count)
+    }
+
+    /// Returns `true` if the item was not in the filter before, that is if any of its bits was newly set.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        let (start, masks) = self.block_masks(item);
+        let mut slice = self.bits.as_bitslice_mut();
+        let block = &mut slice.as_raw_mut_slice()[start..start + BLOCK_WORDS];
+
+        let mut inserted = false;
+        for (word, &mask) in block.iter_mut().zip(&masks) {
+            inserted |= *word & mask != mask;
+            *word |= mask;
+        }
+        inserted
+    }
+
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        let (start, masks) = self.block_masks(item);
+        let block = &self.bits.as_words()[start..start + BLOCK_WORDS];
+        block.iter().zip(&masks).all(|(&word, &mask)| word & mask == mask)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.count_ones() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits = BitVector::with_capacity(self.bits.capacity(), false);
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.bits.capacity() / BLOCK_BITS
+    }
+
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    pub fn as_bit_vector(&self) -> &BitVector<u64> {
+        &self.bits
+    }
+
+    // returns the index of the first word of the block and the bits of the item in each of its words
+    fn block_masks<T: Hash + ?Sized>(&self, item: &T) -> (usize, [u64; BLOCK_WORDS]) {
+        let (first, second) = hash_pair(item);
+        let block_index = (first % self.block_count() as u64) as usize;
+        // an odd step visits distinct positions for the first 512 probes
+        let step = (first >> 32) | 1;
+
+        let mut masks = [0u64; BLOCK_WORDS];
+        for index in 0..self.hash_count as u64 {
+            let position = (second.wrapping_add(index.wrapping_mul(step)) % BLOCK_BITS as u64) as usize;
+            masks[position / 64] |= 1 << (position % 64);
+        }
+        (block_index * BLOCK_WORDS, masks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockedBloomFilter;
+
+    #[test]
+    fn test_with_false_positive_rate() {
+        let filter = BlockedBloomFilter::with_false_positive_rate(1000, 0.01);
+        assert_eq!(filter.block_count(), 19);
+        assert_eq!(filter.hash_count(), 7);
+        assert_eq!(filter.as_bit_vector().capacity(), 19 * 512);
+    }
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut filter = BlockedBloomFilter::new(4, 5);
+        assert_eq!(filter.insert("apple"), true);
+        assert_eq!(filter.insert("apple"), false);
+        assert_eq!(filter.contains("apple"), true);
+        assert_eq!(filter.contains("pear"), false);
+        assert_eq!(filter.as_bit_vector().count_ones(), 5);
+
+        filter.clear();
+        assert_eq!(filter.contains("apple"), false);
+        assert_eq!(filter.is_empty(), true);
+    }
+
+    #[test]
+    fn test_bits_stay_in_one_block() {
+        let mut filter = BlockedBloomFilter::new(16, 8);
+        filter.insert(&12345u64);
+
+        let ones: Vec<_> = filter.as_bit_vector().iter_ones().collect();
+        assert_eq!(ones.len(), 8);
+        assert_eq!(ones[0] / 512, ones[7] / 512);
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = BlockedBloomFilter::with_false_positive_rate(1000, 0.01);
+        for item in 0..1000u32 {
+            filter.insert(&item);
+        }
+        assert!((0..1000u32).all(|item| filter.contains(&item)));
+        let false_positives = (1000..101000u32).filter(|item| filter.contains(item)).count();
+        assert!(false_positives < 2000, "False positives = {}", false_positives);
+    }
+}
@@ -0,0 +1,325 @@
+use std::error::Error;
+use std::f64::consts::LN_2;
+use std::fmt;
+use std::hash::{Hash,Hasher};
+use std::io::{self,Read,Write};
+
+use BitStorage;
+use BitVector;
+use FormatError;
+
+/// A probabilistic set that answers `contains` with no false negatives and a tunable rate of false positives.
+///
+/// Each item sets `hash_count` bits chosen by double hashing, `h1 + i * h2` for `i` in `0..hash_count`. The hash is
+/// fixed rather than `DefaultHasher`, so filters written by `write_to` can be read back by another process, as long
+/// as the items hash the same way on both machines.
+#[derive(Clone,Debug)]
+pub struct BloomFilter<S: BitStorage> {
+    bits: BitVector<S>,
+    hash_count: u32
+}
+
+impl<S: BitStorage> BloomFilter<S> {
+    pub fn new(bit_count: usize, hash_count: u32) -> BloomFilter<S> {
+        assert!(bit_count > 0, "Bloom filter needs at least one bit.");
+        assert!(hash_count > 0, "Bloom filter needs at least one hash function.");
+        BloomFilter {
+            bits: BitVector::with_capacity(bit_count, false),
+            hash_count
+        }
+    }
+
+    /// Sizes the filter for `item_count` items with a false positive rate of at most `false_positive_rate`.
+    pub fn with_false_positive_rate(item_count: usize, false_positive_rate: f64) -> BloomFilter<S> {
+        let (bit_count, hash_count) = optimal_parameters(item_count, false_positive_rate);
+        BloomFilter::new(bit_count, hash_count)
+    }
+
+    /// Returns `true` if the item was not in the filter before, that is if any of its bits was newly set.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        let mut inserted = false;
+        for index in self.indices(item) {
+            if !self.bits[index] {
+                self.bits.set(index, true);
+                inserted = true;
+            }
+        }
+        inserted
+    }
+
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.indices(item).all(|index| self.bits[index])
+    }
+
+    /// Returns a filter that contains every item of either filter, both filters must have the same parameters.
+    pub fn union(&self, other: &BloomFilter<S>) -> BloomFilter<S> {
+        self.combine(other, |left, right| left | right)
+    }
+
+    /// Returns a filter that contains the items of both filters, both filters must have the same parameters.
+    ///
+    /// The false positive rate is at least that of a filter built from the common items alone.
+    pub fn intersection(&self, other: &BloomFilter<S>) -> BloomFilter<S> {
+        self.combine(other, |left, right| left & right)
+    }
+
+    /// Estimates the number of distinct items inserted from the fraction of set bits.
+    pub fn estimated_len(&self) -> f64 {
+        let bit_count = self.bits.capacity() as f64;
+        let ones = self.bits.count_ones() as f64;
+        if ones == bit_count {
+            return f64::INFINITY;
+        }
+        -bit_count / self.hash_count as f64 * (1.0 - ones / bit_count).ln()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.count_ones() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits = BitVector::with_capacity(self.bits.capacity(), false);
+    }
+
+    pub fn bit_count(&self) -> usize {
+        self.bits.capacity()
+    }
+
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    pub fn as_bit_vector(&self) -> &BitVector<S> {
+        &self.bits
+    }
+
+    /// Writes the number of hash functions as a little endian `u32`, followed by the bits in the format of
+    /// `BitVector::write_to`.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.hash_count.to_le_bytes())?;
+        self.bits.write_to(writer)
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<BloomFilter<S>, BloomFilterError> {
+        let mut hash_count = [0u8; 4];
+        reader.read_exact(&mut hash_count)?;
+        let hash_count = u32::from_le_bytes(hash_count);
+        let bits = BitVector::read_from(reader)?;
+        if hash_count == 0 || bits.capacity() == 0 {
+            return Err(BloomFilterError::InvalidParameters { bit_count: bits.capacity(), hash_count });
+        }
+        Ok(BloomFilter {
+            bits,
+            hash_count
+        })
+    }
+
+    fn indices<T: Hash + ?Sized>(&self, item: &T) -> impl Iterator<Item = usize> {
+        let (first, second) = hash_pair(item);
+        let bit_count = self.bits.capacity() as u64;
+        (0..self.hash_count as u64).map(move |index| (first.wrapping_add(index.wrapping_mul(second)) % bit_count) as usize)
+    }
+
+    fn combine(&self, other: &BloomFilter<S>, operation: fn(S, S) -> S) -> BloomFilter<S> {
+        assert!(self.bits.capacity() == other.bits.capacity() && self.hash_count == other.hash_count,
+            "Bloom filter parameters differ. Bit counts = ({}, {}), Hash counts = ({}, {})",
+            self.bits.capacity(), other.bits.capacity(), self.hash_count, other.hash_count);
+        let data = self.bits.as_words().iter().zip(other.bits.as_words()).map(|(&left, &right)| operation(left, right)).collect();
+        BloomFilter {
+            bits: BitVector::from_vec(data, self.bits.capacity()),
+            hash_count: self.hash_count
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BloomFilterError {
+    Format(FormatError),
+    InvalidParameters { bit_count: usize, hash_count: u32 }
+}
+
+impl fmt::Display for BloomFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BloomFilterError::Format(ref error) => write!(f, "Invalid bit vector: {}", error),
+            BloomFilterError::InvalidParameters { bit_count, hash_count } => write!(f, "Invalid filter parameters. Bit count = {}, Hash count = {}", bit_count, hash_count)
+        }
+    }
+}
+
+impl Error for BloomFilterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BloomFilterError::Format(ref error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<FormatError> for BloomFilterError {
+    fn from(error: FormatError) -> BloomFilterError {
+        BloomFilterError::Format(error)
+    }
+}
+
+impl From<io::Error> for BloomFilterError {
+    fn from(error: io::Error) -> BloomFilterError {
+        BloomFilterError::Format(FormatError::Io(error))
+    }
+}
+
+// the bit count and hash count that minimize the size of a filter holding `item_count` items at the given rate
+pub(crate) fn optimal_parameters(item_count: usize, false_positive_rate: f64) -> (usize, u32) {
+    assert!(false_positive_rate > 0.0 && false_positive_rate < 1.0,
+        "False positive rate must be between 0 and 1. Rate = {}", false_positive_rate);
+    let item_count = item_count.max(1) as f64;
+    let bit_count = (-item_count * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+    let hash_count = (bit_count / item_count * LN_2).round().max(1.0);
+    (bit_count as usize, hash_count as u32)
+}
+
+// the second hash is odd so that it never degenerates to probing a single bit
+pub(crate) fn hash_pair<T: Hash + ?Sized>(item: &T) -> (u64, u64) {
+    let mut hasher = Fnv1aHasher::new();
+    item.hash(&mut hasher);
+    let hash = hasher.finish();
+    (mix(hash), mix(hash ^ 0x9e37_79b9_7f4a_7c15) | 1)
+}
+
+// FNV-1a over the bytes fed by `Hash`, finished with `mix` because its low bits are weak
+struct Fnv1aHasher {
+    state: u64
+}
+
+impl Fnv1aHasher {
+    fn new() -> Fnv1aHasher {
+        Fnv1aHasher {
+            state: 0xcbf2_9ce4_8422_2325
+        }
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+// the splitmix64 finalizer
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::FormatError;
+    use super::{BloomFilter,BloomFilterError};
+
+    fn create_filter_u64() -> BloomFilter<u64> {
+        let mut filter = BloomFilter::with_false_positive_rate(1000, 0.01);
+        for item in 0..1000u32 {
+            filter.insert(&item);
+        }
+        filter
+    }
+
+    #[test]
+    fn test_with_false_positive_rate() {
+        let filter = BloomFilter::<u64>::with_false_positive_rate(1000, 0.01);
+        assert_eq!(filter.bit_count(), 9586);
+        assert_eq!(filter.hash_count(), 7);
+        assert_eq!(filter.is_empty(), true);
+    }
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut filter = BloomFilter::<u8>::new(1000, 3);
+        assert_eq!(filter.insert("apple"), true);
+        assert_eq!(filter.insert("apple"), false);
+        assert_eq!(filter.insert(&42u64), true);
+        assert_eq!(filter.contains("apple"), true);
+        assert_eq!(filter.contains(&42u64), true);
+        assert_eq!(filter.contains("pear"), false);
+        assert_eq!(filter.as_bit_vector().count_ones(), 6);
+
+        filter.clear();
+        assert_eq!(filter.contains("apple"), false);
+        assert_eq!(filter.is_empty(), true);
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        let filter = create_filter_u64();
+        for item in 0..1000u32 {
+            assert_eq!(filter.contains(&item), true);
+        }
+        let false_positives = (1000..101000u32).filter(|item| filter.contains(item)).count();
+        assert!(false_positives < 1500, "False positives = {}", false_positives);
+    }
+
+    #[test]
+    fn test_estimated_len() {
+        let filter = create_filter_u64();
+        let estimate = filter.estimated_len();
+        assert!(estimate > 950.0 && estimate < 1050.0, "Estimate = {}", estimate);
+        assert_eq!(BloomFilter::<u32>::new(100, 2).estimated_len(), 0.0);
+    }
+
+    #[test]
+    fn test_union_and_intersection() {
+        let mut left = BloomFilter::<u32>::with_false_positive_rate(200, 0.001);
+        let mut right = left.clone();
+        for item in 0..100u32 {
+            left.insert(&item);
+            right.insert(&(item + 50));
+        }
+
+        let union = left.union(&right);
+        assert!((0..150u32).all(|item| union.contains(&item)));
+        assert_eq!(union.as_bit_vector().count_ones() >= left.as_bit_vector().count_ones(), true);
+
+        let intersection = left.intersection(&right);
+        assert!((50..100u32).all(|item| intersection.contains(&item)));
+        assert!((0..50u32).filter(|item| intersection.contains(item)).count() < 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bloom filter parameters differ. Bit counts = (100, 100), Hash counts = (2, 3)")]
+    fn test_union_parameters_differ() {
+        BloomFilter::<u32>::new(100, 2).union(&BloomFilter::new(100, 3));
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let filter = create_filter_u64();
+        let mut bytes = vec![];
+        filter.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes[..4], [7, 0, 0, 0]);
+
+        let read = BloomFilter::<u64>::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.hash_count(), 7);
+        assert_eq!(read.as_bit_vector().as_words(), filter.as_bit_vector().as_words());
+        assert!((0..1000u32).all(|item| read.contains(&item)));
+
+        bytes[..4].copy_from_slice(&[0, 0, 0, 0]);
+        match BloomFilter::<u64>::read_from(&bytes[..]) {
+            Err(BloomFilterError::InvalidParameters { bit_count: 9586, hash_count: 0 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        match BloomFilter::<u64>::read_from(&bytes[..20]) {
+            Err(BloomFilterError::Format(FormatError::Io(_))) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+}
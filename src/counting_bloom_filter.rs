@@ -0,0 +1,159 @@
+use std::hash::Hash;
+
+use bloom_filter::hash_pair;
+use BitVector;
+
+const COUNTER_BITS: usize = 4;
+const COUNTERS_PER_WORD: usize = 64 / COUNTER_BITS;
+const COUNTER_MAX: u64 = (1 << COUNTER_BITS) - 1;
+
+/// A Bloom filter with a 4 bit counter in place of every bit, which makes it possible to `remove` items.
+///
+/// The counters are packed into a `BitVector<u64>`, 16 to a word. A counter that reaches 15 sticks there, since
+/// decrementing it could otherwise drop items that are still in the filter.
+#[derive(Clone,Debug)]
+pub struct CountingBloomFilter {
+    counters: BitVector<u64>,
+    counter_count: usize,
+    hash_count: u32
+}
+
+impl CountingBloomFilter {
+    pub fn new(counter_count: usize, hash_count: u32) -> CountingBloomFilter {
+        assert!(counter_count > 0, "Bloom filter needs at least one counter.");
+        assert!(hash_count > 0, "Bloom filter needs at least one hash function.");
+        CountingBloomFilter {
+            counters: BitVector::with_capacity(counter_count * COUNTER_BITS, false),
+            counter_count,
+            hash_count
+        }
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for index in self.indices(item) {
+            let counter = self.counter(index);
+            if counter < COUNTER_MAX {
+                self.set_counter(index, counter + 1);
+            }
+        }
+    }
+
+    /// Returns `false` and leaves the filter unchanged if the item is not in the filter.
+    ///
+    /// Removing an item that was never inserted but happens to be a false positive corrupts the filter.
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        for index in self.indices(item) {
+            let counter = self.counter(index);
+            // an index can repeat for the same item, so it may already have reached zero
+            if counter > 0 && counter < COUNTER_MAX {
+                self.set_counter(index, counter - 1);
+            }
+        }
+        true
+    }
+
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.indices(item).all(|index| self.counter(index) > 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.count_ones() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.counters = BitVector::with_capacity(self.counters.capacity(), false);
+    }
+
+    pub fn counter_count(&self) -> usize {
+        self.counter_count
+    }
+
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    fn indices<T: Hash + ?Sized>(&self, item: &T) -> impl Iterator<Item = usize> {
+        let (first, second) = hash_pair(item);
+        let counter_count = self.counter_count as u64;
+        (0..self.hash_count as u64).map(move |index| (first.wrapping_add(index.wrapping_mul(second)) % counter_count) as usize)
+    }
+
+    #[inline]
+    fn counter(&self, index: usize) -> u64 {
+        let word = self.counters.as_words()[index / COUNTERS_PER_WORD];
+        (word >> (index % COUNTERS_PER_WORD * COUNTER_BITS)) & COUNTER_MAX
+    }
+
+    #[inline]
+    fn set_counter(&mut self, index: usize, value: u64) {
+        let shift = index % COUNTERS_PER_WORD * COUNTER_BITS;
+        let mut slice = self.counters.as_bitslice_mut();
+        let word = &mut slice.as_raw_mut_slice()[index / COUNTERS_PER_WORD];
+        *word = (*word & !(COUNTER_MAX << shift)) | (value << shift);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountingBloomFilter;
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut filter = CountingBloomFilter::new(1000, 4);
+        filter.insert("apple");
+        filter.insert("pear");
+        assert_eq!(filter.contains("apple"), true);
+        assert_eq!(filter.contains("pear"), true);
+
+        assert_eq!(filter.remove("apple"), true);
+        assert_eq!(filter.contains("apple"), false);
+        assert_eq!(filter.contains("pear"), true);
+        assert_eq!(filter.remove("apple"), false);
+
+        assert_eq!(filter.remove("pear"), true);
+        assert_eq!(filter.is_empty(), true);
+    }
+
+    #[test]
+    fn test_insert_twice() {
+        let mut filter = CountingBloomFilter::new(100, 3);
+        filter.insert(&7u32);
+        filter.insert(&7u32);
+        assert_eq!(filter.remove(&7u32), true);
+        assert_eq!(filter.contains(&7u32), true);
+        assert_eq!(filter.remove(&7u32), true);
+        assert_eq!(filter.contains(&7u32), false);
+    }
+
+    #[test]
+    fn test_counters_saturate() {
+        let mut filter = CountingBloomFilter::new(17, 1);
+        for _ in 0..20 {
+            filter.insert(&1u8);
+        }
+        for _ in 0..20 {
+            filter.remove(&1u8);
+        }
+        assert_eq!(filter.contains(&1u8), true);
+
+        filter.clear();
+        assert_eq!(filter.is_empty(), true);
+        assert_eq!(filter.counter_count(), 17);
+    }
+
+    #[test]
+    fn test_many_items() {
+        let mut filter = CountingBloomFilter::new(10000, 7);
+        for item in 0..1000u32 {
+            filter.insert(&item);
+        }
+        for item in 0..500u32 {
+            assert_eq!(filter.remove(&item), true);
+        }
+        assert!((500..1000u32).all(|item| filter.contains(&item)));
+        assert!((0..500u32).filter(|item| filter.contains(item)).count() < 50);
+    }
+}
@@ -0,0 +1,509 @@
+use std::cmp;
+
+use BitStorage;
+use BitVector;
+
+/// A bit vector compressed with the Enhanced Word-Aligned Hybrid scheme.
+///
+/// The buffer is a sequence of marker words, each followed by its literal words. A marker holds the value of a run of
+/// clean words (all zeros or all ones) in its lowest bit, the length of that run in the lower half of the remaining
+/// bits and the number of literal words following the run in the upper half. Logical operations work directly on the
+/// runs and literals of both operands.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct EwahBitmap<S: BitStorage> {
+    buffer: Vec<S>,
+    len: usize
+}
+
+impl<S: BitStorage> EwahBitmap<S> {
+    pub fn from_bit_vector(bit_vector: &BitVector<S>) -> EwahBitmap<S> {
+        let mut builder = Builder::new();
+        for &element in bit_vector.as_words() {
+            builder.add_literal(element);
+        }
+        builder.finish(bit_vector.capacity())
+    }
+
+    pub fn to_bit_vector(&self) -> BitVector<S> {
+        let mut data = Vec::with_capacity(self.len.div_ceil(S::storage_size()));
+        let mut cursor = Cursor::new(&self.buffer);
+        while let Some(chunk) = cursor.peek() {
+            match chunk {
+                Chunk::Fill(value, count) => {
+                    data.extend((0..count).map(|_| fill_element::<S>(value)));
+                    cursor.consume(count);
+                },
+                Chunk::Literal(element) => {
+                    data.push(element);
+                    cursor.consume(1);
+                }
+            }
+        }
+        BitVector::from_vec(data, self.len)
+    }
+
+    /// Returns the number of bits, not the size of the compressed buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_words(&self) -> &[S] {
+        &self.buffer
+    }
+
+    pub fn count_ones(&self) -> usize {
+        let mut count = 0;
+        let mut cursor = Cursor::new(&self.buffer);
+        while let Some(chunk) = cursor.peek() {
+            match chunk {
+                Chunk::Fill(value, count_words) => {
+                    if value {
+                        count += count_words * S::storage_size();
+                    }
+                    cursor.consume(count_words);
+                },
+                Chunk::Literal(element) => {
+                    count += element.count_ones() as usize;
+                    cursor.consume(1);
+                }
+            }
+        }
+        count
+    }
+
+    pub fn iter_ones(&self) -> IterOnes<'_, S> {
+        IterOnes {
+            cursor: Cursor::new(&self.buffer),
+            word_index: 0,
+            element: S::ZERO,
+            fill_index: 0,
+            fill_end: 0
+        }
+    }
+
+    pub fn and(&self, other: &EwahBitmap<S>) -> EwahBitmap<S> {
+        self.combine(other, |left, right| left & right)
+    }
+
+    pub fn or(&self, other: &EwahBitmap<S>) -> EwahBitmap<S> {
+        self.combine(other, |left, right| left | right)
+    }
+
+    pub fn xor(&self, other: &EwahBitmap<S>) -> EwahBitmap<S> {
+        self.combine(other, |left, right| left ^ right)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(&self) -> EwahBitmap<S> {
+        let word_count = self.len.div_ceil(S::storage_size());
+        let (_, remainder) = S::compute_data_index_and_remainder(self.len);
+        // the padding bits of the last element have to stay zero
+        let last_mask = if remainder == 0 { S::MAX } else { S::mask(0, remainder as usize) };
+
+        let mut builder = Builder::new();
+        let mut word_index = 0;
+        let mut cursor = Cursor::new(&self.buffer);
+        while let Some(chunk) = cursor.peek() {
+            match chunk {
+                Chunk::Fill(value, count) => {
+                    if word_index + count == word_count {
+                        builder.add_fill(!value, count - 1);
+                        builder.add_literal(fill_element::<S>(!value) & last_mask);
+                    }
+                    else {
+                        builder.add_fill(!value, count);
+                    }
+                    cursor.consume(count);
+                    word_index += count;
+                },
+                Chunk::Literal(element) => {
+                    let mask = if word_index + 1 == word_count { last_mask } else { S::MAX };
+                    builder.add_literal(!element & mask);
+                    cursor.consume(1);
+                    word_index += 1;
+                }
+            }
+        }
+        builder.finish(self.len)
+    }
+
+    // the shorter operand is extended with zero words
+    fn combine(&self, other: &EwahBitmap<S>, operation: fn(S, S) -> S) -> EwahBitmap<S> {
+        let len = cmp::max(self.len, other.len);
+        let mut words_left = len.div_ceil(S::storage_size());
+
+        let mut builder = Builder::new();
+        let (mut left, mut right) = (Cursor::new(&self.buffer), Cursor::new(&other.buffer));
+        while words_left > 0 {
+            let left_chunk = left.peek().unwrap_or(Chunk::Fill(false, words_left));
+            let right_chunk = right.peek().unwrap_or(Chunk::Fill(false, words_left));
+            let count = match (left_chunk, right_chunk) {
+                (Chunk::Fill(left_value, left_count), Chunk::Fill(right_value, right_count)) => {
+                    let count = cmp::min(left_count, right_count);
+                    let element = operation(fill_element(left_value), fill_element(right_value));
+                    builder.add_fill(element != S::ZERO, count);
+                    count
+                },
+                (left_chunk, right_chunk) => {
+                    builder.add_literal(operation(left_chunk.first_element(), right_chunk.first_element()));
+                    1
+                }
+            };
+            left.consume(count);
+            right.consume(count);
+            words_left -= count;
+        }
+        builder.finish(len)
+    }
+}
+
+/// Iterates over the indices of the set bits.
+pub struct IterOnes<'a, S: BitStorage + 'a> {
+    cursor: Cursor<'a, S>,
+    word_index: usize,
+    element: S,
+    fill_index: usize,
+    fill_end: usize
+}
+
+impl<'a, S: BitStorage + 'a> Iterator for IterOnes<'a, S> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if self.fill_index < self.fill_end {
+                self.fill_index += 1;
+                return Some(self.fill_index - 1);
+            }
+            // the bits left in the last literal, which is the element before word_index
+            if self.element != S::ZERO {
+                let remainder = self.element.trailing_zeros();
+                S::set(&mut self.element, remainder, false);
+                return Some((self.word_index - 1) * S::storage_size() + remainder as usize);
+            }
+
+            match self.cursor.peek()? {
+                Chunk::Fill(value, count) => {
+                    if value {
+                        self.fill_index = self.word_index * S::storage_size();
+                        self.fill_end = (self.word_index + count) * S::storage_size();
+                    }
+                    self.word_index += count;
+                    self.cursor.consume(count);
+                },
+                Chunk::Literal(element) => {
+                    self.element = element;
+                    self.word_index += 1;
+                    self.cursor.consume(1);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone,Copy)]
+enum Chunk<S: BitStorage> {
+    Fill(bool, usize),
+    Literal(S)
+}
+
+impl<S: BitStorage> Chunk<S> {
+    #[inline]
+    fn first_element(self) -> S {
+        match self {
+            Chunk::Fill(value, _) => fill_element(value),
+            Chunk::Literal(element) => element
+        }
+    }
+}
+
+// reads the buffer a run or literal at a time, runs can be consumed partially
+struct Cursor<'a, S: BitStorage + 'a> {
+    buffer: &'a [S],
+    position: usize,
+    running_bit: bool,
+    running_len: usize,
+    literal_count: usize
+}
+
+impl<'a, S: BitStorage + 'a> Cursor<'a, S> {
+    fn new(buffer: &'a [S]) -> Cursor<'a, S> {
+        Cursor {
+            buffer,
+            position: 0,
+            running_bit: false,
+            running_len: 0,
+            literal_count: 0
+        }
+    }
+
+    fn peek(&mut self) -> Option<Chunk<S>> {
+        while self.running_len == 0 && self.literal_count == 0 {
+            let marker = *self.buffer.get(self.position)?;
+            let (running_bit, running_len, literal_count) = decode_marker(marker);
+            self.running_bit = running_bit;
+            self.running_len = running_len;
+            self.literal_count = literal_count;
+            self.position += 1;
+        }
+        if self.running_len > 0 {
+            Some(Chunk::Fill(self.running_bit, self.running_len))
+        }
+        else {
+            Some(Chunk::Literal(self.buffer[self.position]))
+        }
+    }
+
+    // consumes words of the chunk returned by the last call to peek
+    fn consume(&mut self, count: usize) {
+        if self.running_len > 0 {
+            self.running_len -= count;
+        }
+        else if self.literal_count > 0 {
+            self.literal_count -= 1;
+            self.position += 1;
+        }
+    }
+}
+
+// appends runs and literals, merging runs and turning clean literals into runs so that the encoding stays canonical
+struct Builder<S: BitStorage> {
+    buffer: Vec<S>,
+    marker_position: usize
+}
+
+impl<S: BitStorage> Builder<S> {
+    fn new() -> Builder<S> {
+        Builder {
+            buffer: vec![S::ZERO],
+            marker_position: 0
+        }
+    }
+
+    fn add_fill(&mut self, value: bool, mut count: usize) {
+        while count > 0 {
+            let (running_bit, running_len, literal_count) = decode_marker(self.buffer[self.marker_position]);
+            let can_extend = literal_count == 0 && (running_len == 0 || running_bit == value) && running_len < max_running_len::<S>();
+            if !can_extend {
+                self.start_marker();
+                continue;
+            }
+            let added = cmp::min(count, max_running_len::<S>() - running_len);
+            self.buffer[self.marker_position] = encode_marker(value, running_len + added, 0);
+            count -= added;
+        }
+    }
+
+    fn add_literal(&mut self, element: S) {
+        if element == S::ZERO || element == S::MAX {
+            self.add_fill(element != S::ZERO, 1);
+            return;
+        }
+        let (running_bit, running_len, literal_count) = decode_marker(self.buffer[self.marker_position]);
+        if literal_count == max_literal_count::<S>() {
+            self.start_marker();
+            self.buffer[self.marker_position] = encode_marker(false, 0, 1);
+        }
+        else {
+            self.buffer[self.marker_position] = encode_marker(running_bit, running_len, literal_count + 1);
+        }
+        self.buffer.push(element);
+    }
+
+    #[inline]
+    fn start_marker(&mut self) {
+        self.marker_position = self.buffer.len();
+        self.buffer.push(S::ZERO);
+    }
+
+    fn finish(self, len: usize) -> EwahBitmap<S> {
+        EwahBitmap {
+            buffer: self.buffer,
+            len
+        }
+    }
+}
+
+#[inline]
+fn fill_element<S: BitStorage>(value: bool) -> S {
+    if value { S::MAX } else { S::ZERO }
+}
+
+#[inline]
+fn running_len_bits<S: BitStorage>() -> usize {
+    S::storage_size() / 2
+}
+
+#[inline]
+fn max_running_len<S: BitStorage>() -> usize {
+    max_count(running_len_bits::<S>())
+}
+
+#[inline]
+fn max_literal_count<S: BitStorage>() -> usize {
+    max_count(S::storage_size() - 1 - running_len_bits::<S>())
+}
+
+// the counts of wide storage types are limited to what a usize holds
+#[inline]
+fn max_count(bits: usize) -> usize {
+    if bits >= usize::BITS as usize { usize::MAX } else { (1 << bits) - 1 }
+}
+
+#[inline]
+fn encode_marker<S: BitStorage>(running_bit: bool, running_len: usize, literal_count: usize) -> S {
+    let literal_count_shift = 1 + running_len_bits::<S>() as u32;
+    let running_bit = if running_bit { S::ONE } else { S::ZERO };
+    running_bit | (S::from_u64(running_len as u64) << 1) | (S::from_u64(literal_count as u64) << literal_count_shift)
+}
+
+#[inline]
+fn decode_marker<S: BitStorage>(marker: S) -> (bool, usize, usize) {
+    let literal_count_shift = 1 + running_len_bits::<S>() as u32;
+    let running_bit = marker & S::ONE == S::ONE;
+    let running_len = ((marker >> 1) & S::mask(0, running_len_bits::<S>())).to_u64() as usize;
+    let literal_count = (marker >> literal_count_shift).to_u64() as usize;
+    (running_bit, running_len, literal_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BitVector;
+    use super::{EwahBitmap,decode_marker,encode_marker};
+
+    fn create_bitvector_u8(capacity: usize, ones: &[usize]) -> BitVector<u8> {
+        let mut vec = BitVector::with_capacity(capacity, false);
+        for &index in ones {
+            vec.set(index, true);
+        }
+        vec
+    }
+
+    fn create_sparse_bitvector_u32() -> BitVector<u32> {
+        let mut vec = BitVector::with_capacity(10000, false);
+        vec.set(5, true);
+        vec.set(6, true);
+        for index in 3000..5000 {
+            vec.set(index, true);
+        }
+        vec.set(9999, true);
+        vec
+    }
+
+    #[test]
+    fn test_marker() {
+        assert_eq!(decode_marker(0b00100111u8), (true, 3, 1));
+        assert_eq!(decode_marker(0b11111110u8), (false, 15, 7));
+        assert_eq!(decode_marker(u64::MAX), (true, 0xffffffff, 0x7fffffff));
+        assert_eq!(decode_marker(encode_marker::<u128>(false, 1 << 40, 3)), (false, 1 << 40, 3));
+    }
+
+    #[test]
+    fn test_from_bit_vector() {
+        let vec = create_bitvector_u8(40, &[3, 8, 9, 10, 11, 12, 13, 14, 15, 39]);
+        let ewah = EwahBitmap::from_bit_vector(&vec);
+
+        // a marker with one literal, a marker for the set word and a marker for two zero words and the last literal
+        assert_eq!(ewah.as_words(), [0b00100000, 0b00001000, 0b00000011, 0b00100100, 0b10000000]);
+        assert_eq!(ewah.len(), 40);
+        assert_eq!(ewah.count_ones(), 10);
+        assert_eq!(ewah.to_bit_vector().as_words(), vec.as_words());
+    }
+
+    #[test]
+    fn test_compression() {
+        let vec = create_sparse_bitvector_u32();
+        let ewah = EwahBitmap::from_bit_vector(&vec);
+
+        assert!(ewah.as_words().len() < 10);
+        assert_eq!(ewah.count_ones(), 2003);
+        assert_eq!(ewah.to_bit_vector().as_words(), vec.as_words());
+    }
+
+    #[test]
+    fn test_long_runs_split_markers() {
+        let mut vec = BitVector::<u8>::with_capacity(2000, true);
+        for index in (1000..1200).step_by(3) {
+            vec.set(index, false);
+        }
+        let ewah = EwahBitmap::from_bit_vector(&vec);
+
+        assert_eq!(ewah.count_ones(), vec.count_ones());
+        assert_eq!(ewah.to_bit_vector().as_words(), vec.as_words());
+        let ones: Vec<_> = ewah.iter_ones().collect();
+        let expected: Vec<_> = vec.iter_ones().collect();
+        assert_eq!(ones, expected);
+    }
+
+    #[test]
+    fn test_u128() {
+        let mut vec = BitVector::<u128>::with_capacity(1000, false);
+        for index in (130..700).chain(900..905) {
+            vec.set(index, true);
+        }
+        let ewah = EwahBitmap::from_bit_vector(&vec);
+        assert_eq!(ewah.count_ones(), 575);
+        assert_eq!(ewah.to_bit_vector().as_words(), vec.as_words());
+        assert_eq!(ewah.iter_ones().collect::<Vec<_>>(), vec.iter_ones().collect::<Vec<_>>());
+        assert_eq!(ewah.not().count_ones(), 425);
+    }
+
+    #[test]
+    fn test_iter_ones() {
+        let vec = create_bitvector_u8(40, &[3, 8, 9, 10, 11, 12, 13, 14, 15, 39]);
+        let ones: Vec<_> = EwahBitmap::from_bit_vector(&vec).iter_ones().collect();
+        assert_eq!(ones, [3, 8, 9, 10, 11, 12, 13, 14, 15, 39]);
+
+        let vec = create_sparse_bitvector_u32();
+        let ones: Vec<_> = EwahBitmap::from_bit_vector(&vec).iter_ones().collect();
+        let expected: Vec<_> = vec.iter_ones().collect();
+        assert_eq!(ones, expected);
+    }
+
+    #[test]
+    fn test_logical_operations() {
+        let left = create_sparse_bitvector_u32();
+        let mut right = BitVector::<u32>::with_capacity(12000, false);
+        for index in (0..12000).step_by(7).chain(4000..8000) {
+            right.set(index, true);
+        }
+        let (left_ewah, right_ewah) = (EwahBitmap::from_bit_vector(&left), EwahBitmap::from_bit_vector(&right));
+
+        let expected = |operation: fn(bool, bool) -> bool| -> Vec<usize> {
+            (0..12000).filter(|&index| operation(left.get(index).unwrap_or(false), right.get(index).unwrap_or(false))).collect()
+        };
+
+        let and = left_ewah.and(&right_ewah);
+        assert_eq!(and.len(), 12000);
+        assert_eq!(and.iter_ones().collect::<Vec<_>>(), expected(|l, r| l && r));
+        assert_eq!(left_ewah.or(&right_ewah).iter_ones().collect::<Vec<_>>(), expected(|l, r| l || r));
+        assert_eq!(right_ewah.xor(&left_ewah).iter_ones().collect::<Vec<_>>(), expected(|l, r| l != r));
+
+        assert_eq!(left_ewah.and(&left_ewah), left_ewah);
+        assert_eq!(left_ewah.xor(&left_ewah).count_ones(), 0);
+        assert_eq!(left_ewah.xor(&left_ewah).as_words().len(), 1);
+    }
+
+    #[test]
+    fn test_not() {
+        let vec = create_bitvector_u8(20, &[0, 9]);
+        let not = EwahBitmap::from_bit_vector(&vec).not();
+        assert_eq!(not.len(), 20);
+        assert_eq!(not.count_ones(), 18);
+        assert_eq!(not.to_bit_vector().as_words(), [0b11111110, 0b11111101, 0b00001111]);
+
+        let vec = BitVector::<u8>::with_capacity(20, false);
+        let not = EwahBitmap::from_bit_vector(&vec).not();
+        assert_eq!(not.to_bit_vector().as_words(), [0b11111111, 0b11111111, 0b00001111]);
+        assert_eq!(not.not(), EwahBitmap::from_bit_vector(&vec));
+
+        let vec = create_sparse_bitvector_u32();
+        let not = EwahBitmap::from_bit_vector(&vec).not();
+        assert_eq!(not.count_ones(), 10000 - 2003);
+        assert_eq!(not.not(), EwahBitmap::from_bit_vector(&vec));
+    }
+}
@@ -0,0 +1,354 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self,Read,Write};
+
+use BitStorage;
+use BitOrder;
+use BitVector;
+
+// layout: magic, version, endianness of the payload, bit order, a reserved byte, word size in bits (u32 little endian),
+// bit length (u64 little endian), reserved bytes up to 32, payload of storage elements, CRC32 of everything before it
+// (u32 little endian)
+// the header is padded so that the payload stays aligned for every storage type when the file is memory mapped
+const MAGIC: [u8; 4] = *b"BITV";
+const VERSION: u8 = 1;
+pub(crate) const HEADER_SIZE: usize = 32;
+pub(crate) const CHECKSUM_SIZE: usize = 4;
+
+const LITTLE_ENDIAN: u8 = 0;
+const BIG_ENDIAN: u8 = 1;
+pub(crate) const NATIVE_ENDIANNESS: u8 = if cfg!(target_endian = "big") { BIG_ENDIAN } else { LITTLE_ENDIAN };
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FormatError {
+    Io(io::Error),
+    InvalidMagic([u8; 4]),
+    UnsupportedVersion(u8),
+    WordSizeMismatch { expected: usize, found: usize },
+    BitOrderMismatch { expected: u8, found: u8 },
+    InvalidEndianness(u8),
+    EndiannessMismatch { expected: u8, found: u8 },
+    LengthOverflow(u64),
+    Truncated { expected: u64, found: u64 },
+    ChecksumMismatch { expected: u32, found: u32 },
+    Misaligned { alignment: usize }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatError::Io(ref error) => write!(f, "I/O error: {}", error),
+            FormatError::InvalidMagic(magic) => write!(f, "Invalid magic number. Magic = {:?}", magic),
+            FormatError::UnsupportedVersion(version) => write!(f, "Unsupported format version. Version = {}", version),
+            FormatError::WordSizeMismatch { expected, found } => write!(f, "Word size mismatch. Expected = {}, Found = {}", expected, found),
+            FormatError::BitOrderMismatch { expected, found } => write!(f, "Bit order mismatch. Expected = {}, Found = {}", expected, found),
+            FormatError::InvalidEndianness(endianness) => write!(f, "Invalid endianness. Endianness = {}", endianness),
+            FormatError::EndiannessMismatch { expected, found } => write!(f, "Endianness mismatch. Expected = {}, Found = {}", expected, found),
+            FormatError::LengthOverflow(len) => write!(f, "Length does not fit in memory. Length = {}", len),
+            FormatError::Truncated { expected, found } => write!(f, "Data is truncated. Expected = {}, Found = {}", expected, found),
+            FormatError::ChecksumMismatch { expected, found } => write!(f, "Checksum mismatch. Expected = {:#010x}, Found = {:#010x}", expected, found),
+            FormatError::Misaligned { alignment } => write!(f, "Data is not aligned to the storage type. Alignment = {}", alignment)
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            FormatError::Io(ref error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(error: io::Error) -> FormatError {
+        FormatError::Io(error)
+    }
+}
+
+impl<S: BitStorage, O: BitOrder> BitVector<S, O> {
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut crc32 = Crc32::new();
+
+        let header = Header { endianness: NATIVE_ENDIANNESS, capacity: self.capacity() }.to_bytes::<S, O>();
+        crc32.update(&header);
+        writer.write_all(&header)?;
+
+        for &element in self.as_words() {
+            let bytes = element_to_bytes(element, NATIVE_ENDIANNESS);
+            crc32.update(bytes.as_ref());
+            writer.write_all(bytes.as_ref())?;
+        }
+
+        writer.write_all(&crc32.finish().to_le_bytes())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<BitVector<S, O>, FormatError> {
+        let mut crc32 = Crc32::new();
+
+        let mut header = [0u8; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        crc32.update(&header);
+        let header = Header::from_bytes::<S, O>(&header)?;
+
+        let mut data = Vec::new();
+        for _ in 0..header.capacity.div_ceil(S::storage_size()) {
+            let mut bytes = S::Bytes::default();
+            reader.read_exact(bytes.as_mut())?;
+            crc32.update(bytes.as_ref());
+            data.push(bytes_to_element(bytes, header.endianness));
+        }
+
+        let mut checksum = [0u8; CHECKSUM_SIZE];
+        reader.read_exact(&mut checksum)?;
+        let checksum = u32::from_le_bytes(checksum);
+        let computed = crc32.finish();
+        if checksum != computed {
+            return Err(FormatError::ChecksumMismatch { expected: checksum, found: computed });
+        }
+
+        Ok(BitVector::from_vec(data, header.capacity))
+    }
+}
+
+pub(crate) struct Header {
+    pub(crate) endianness: u8,
+    pub(crate) capacity: usize
+}
+
+impl Header {
+    fn to_bytes<S: BitStorage, O: BitOrder>(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = VERSION;
+        bytes[5] = self.endianness;
+        bytes[6] = O::ORDER_ID;
+        bytes[8..12].copy_from_slice(&(S::storage_size() as u32).to_le_bytes());
+        bytes[12..20].copy_from_slice(&(self.capacity as u64).to_le_bytes());
+        bytes
+    }
+
+    pub(crate) fn from_bytes<S: BitStorage, O: BitOrder>(bytes: &[u8]) -> Result<Header, FormatError> {
+        let mut magic = [0u8; 4];
+        magic.copy_from_slice(&bytes[..4]);
+        if magic != MAGIC {
+            return Err(FormatError::InvalidMagic(magic));
+        }
+        if bytes[4] != VERSION {
+            return Err(FormatError::UnsupportedVersion(bytes[4]));
+        }
+        let endianness = bytes[5];
+        if endianness != LITTLE_ENDIAN && endianness != BIG_ENDIAN {
+            return Err(FormatError::InvalidEndianness(endianness));
+        }
+        if bytes[6] != O::ORDER_ID {
+            return Err(FormatError::BitOrderMismatch { expected: O::ORDER_ID, found: bytes[6] });
+        }
+        let mut word_size = [0u8; 4];
+        word_size.copy_from_slice(&bytes[8..12]);
+        let word_size = u32::from_le_bytes(word_size) as usize;
+        if word_size != S::storage_size() {
+            return Err(FormatError::WordSizeMismatch { expected: S::storage_size(), found: word_size });
+        }
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&bytes[12..20]);
+        let len = u64::from_le_bytes(len);
+        if len > usize::MAX as u64 {
+            return Err(FormatError::LengthOverflow(len));
+        }
+        Ok(Header {
+            endianness,
+            capacity: len as usize
+        })
+    }
+}
+
+#[inline]
+fn element_to_bytes<S: BitStorage>(element: S, endianness: u8) -> S::Bytes {
+    match endianness == BIG_ENDIAN {
+        true => element.swap_bytes().to_le_bytes(),
+        false => element.to_le_bytes()
+    }
+}
+
+#[inline]
+fn bytes_to_element<S: BitStorage>(bytes: S::Bytes, endianness: u8) -> S {
+    let element = S::from_le_bytes(bytes);
+    match endianness == BIG_ENDIAN {
+        true => element.swap_bytes(),
+        false => element
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut value = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 { (value >> 1) ^ 0xedb88320 } else { value >> 1 };
+            bit += 1;
+        }
+        table[index] = value;
+        index += 1;
+    }
+    table
+}
+
+// CRC-32 as used by zlib and PNG
+pub(crate) struct Crc32 {
+    value: u32
+}
+
+impl Crc32 {
+    pub(crate) fn new() -> Crc32 {
+        Crc32 {
+            value: 0xffffffff
+        }
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.value = CRC32_TABLE[((self.value ^ byte as u32) & 0xff) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u32 {
+        !self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Crc32,FormatError,BIG_ENDIAN,HEADER_SIZE};
+    use super::super::{BitVector,Msb0};
+
+    fn create_bitvector_u16_40() -> BitVector<u16> {
+        let mut vec = BitVector::with_capacity(40, false);
+        vec.set(0, true);
+        vec.set(17, true);
+        vec.set(39, true);
+        vec
+    }
+
+    fn write_to_vec<S: ::BitStorage, O: ::BitOrder>(vec: &BitVector<S, O>) -> Vec<u8> {
+        let mut bytes = vec![];
+        vec.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_crc32() {
+        let mut crc32 = Crc32::new();
+        crc32.update(b"123456789");
+        assert_eq!(crc32.finish(), 0xcbf43926);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let vec = create_bitvector_u16_40();
+        let bytes = write_to_vec(&vec);
+        assert_eq!(bytes.len(), HEADER_SIZE + 3 * 2 + 4);
+
+        let read = BitVector::<u16>::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.capacity(), 40);
+        assert_eq!(read.as_words(), vec.as_words());
+    }
+
+    #[test]
+    fn test_round_trip_empty() {
+        let vec = BitVector::<u64, Msb0>::with_capacity(0, false);
+        let bytes = write_to_vec(&vec);
+
+        let read = BitVector::<u64, Msb0>::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.capacity(), 0);
+    }
+
+    #[test]
+    fn test_read_other_endianness() {
+        let vec = create_bitvector_u16_40();
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"BITV");
+        bytes.extend_from_slice(&[1, BIG_ENDIAN, 0, 0]);
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&40u64.to_le_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        for &element in vec.as_words() {
+            bytes.extend_from_slice(&element.to_be_bytes());
+        }
+        let mut crc32 = Crc32::new();
+        crc32.update(&bytes);
+        bytes.extend_from_slice(&crc32.finish().to_le_bytes());
+
+        let read = BitVector::<u16>::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.as_words(), vec.as_words());
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        let mut bytes = write_to_vec(&create_bitvector_u16_40());
+        bytes[0] = b'X';
+
+        match BitVector::<u16>::read_from(&bytes[..]) {
+            Err(FormatError::InvalidMagic(magic)) => assert_eq!(&magic, b"XITV"),
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut bytes = write_to_vec(&create_bitvector_u16_40());
+        bytes[4] = 2;
+
+        match BitVector::<u16>::read_from(&bytes[..]) {
+            Err(FormatError::UnsupportedVersion(2)) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_word_size_mismatch() {
+        let bytes = write_to_vec(&create_bitvector_u16_40());
+
+        match BitVector::<u32>::read_from(&bytes[..]) {
+            Err(FormatError::WordSizeMismatch { expected: 32, found: 16 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_bit_order_mismatch() {
+        let bytes = write_to_vec(&create_bitvector_u16_40());
+
+        match BitVector::<u16, Msb0>::read_from(&bytes[..]) {
+            Err(FormatError::BitOrderMismatch { expected: 1, found: 0 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut bytes = write_to_vec(&create_bitvector_u16_40());
+        bytes[HEADER_SIZE] ^= 0b00000100;
+
+        match BitVector::<u16>::read_from(&bytes[..]) {
+            Err(FormatError::ChecksumMismatch { .. }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_truncated() {
+        let bytes = write_to_vec(&create_bitvector_u16_40());
+
+        match BitVector::<u16>::read_from(&bytes[..bytes.len() - 1]) {
+            Err(FormatError::Io(_)) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+}
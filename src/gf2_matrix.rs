@@ -0,0 +1,330 @@
+use std::fmt;
+
+use BitVector;
+use BitSlice;
+use BitSliceMut;
+use BitMatrix;
+
+/// A matrix over GF(2), the field of two elements where addition is xor and multiplication is and.
+///
+/// Every row is a `BitVector<u64>`, so adding one row to another takes one xor per 64 columns.
+#[derive(Clone)]
+pub struct Gf2Matrix {
+    rows: Vec<BitVector<u64>>,
+    cols: usize
+}
+
+impl Gf2Matrix {
+    pub fn new(rows: usize, cols: usize) -> Gf2Matrix {
+        Gf2Matrix {
+            rows: (0..rows).map(|_| BitVector::with_capacity(cols, false)).collect(),
+            cols
+        }
+    }
+
+    /// Panics if the rows are not all `cols` bits long.
+    pub fn from_rows(rows: Vec<BitVector<u64>>, cols: usize) -> Gf2Matrix {
+        for (index, row) in rows.iter().enumerate() {
+            if row.capacity() != cols {
+                panic!("Row length does not match. Columns = {}, Row = {}, Length = {}", cols, index, row.capacity());
+            }
+        }
+        Gf2Matrix {
+            rows,
+            cols
+        }
+    }
+
+    pub fn identity(size: usize) -> Gf2Matrix {
+        let mut matrix = Gf2Matrix::new(size, size);
+        for index in 0..size {
+            matrix.set(index, index, true);
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<bool> {
+        self.rows.get(row).and_then(|bits| bits.get(col))
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        if row >= self.rows.len() || col >= self.cols {
+            panic!("Index out of bounds. Rows = {}, Columns = {}, Row = {}, Column = {}", self.rows.len(), self.cols, row, col);
+        }
+        self.rows[row].set(col, value);
+    }
+
+    pub fn row(&self, row: usize) -> &BitVector<u64> {
+        &self.rows[row]
+    }
+
+    pub fn into_rows(self) -> Vec<BitVector<u64>> {
+        self.rows
+    }
+
+    /// Brings the matrix into reduced row echelon form and returns the pivot column of every nonzero row.
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        self.row_reduce_with(None)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// Returns an `x` with `self * x = b`, or `None` if the system has no solution. Free variables are set to zero.
+    pub fn solve(&self, b: &BitVector<u64>) -> Option<BitVector<u64>> {
+        if b.capacity() != self.rows.len() {
+            panic!("Vector length does not match. Rows = {}, Length = {}", self.rows.len(), b.capacity());
+        }
+        let mut reduced = self.clone();
+        let mut rhs = Gf2Matrix::from_rows(b.iter().map(|bit| BitVector::with_capacity(1, bit)).collect(), 1);
+        let pivots = reduced.row_reduce_with(Some(&mut rhs));
+        if rhs.rows[pivots.len()..].iter().any(|row| row[0]) {
+            return None;
+        }
+
+        let mut x = BitVector::with_capacity(self.cols, false);
+        for (row, &col) in pivots.iter().enumerate() {
+            x.set(col, rhs.rows[row][0]);
+        }
+        Some(x)
+    }
+
+    /// Returns a basis of the vectors `x` with `self * x = 0`, one for every free column.
+    pub fn nullspace(&self) -> Vec<BitVector<u64>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+
+        let mut is_pivot = BitVector::<u64>::with_capacity(self.cols, false);
+        for &col in &pivots {
+            is_pivot.set(col, true);
+        }
+        (0..self.cols).filter(|&col| !is_pivot[col]).map(|free_col| {
+            let mut basis_vector = BitVector::with_capacity(self.cols, false);
+            basis_vector.set(free_col, true);
+            for (row, &col) in pivots.iter().enumerate() {
+                basis_vector.set(col, reduced.rows[row][free_col]);
+            }
+            basis_vector
+        }).collect()
+    }
+
+    /// Returns `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Gf2Matrix> {
+        if self.rows.len() != self.cols {
+            panic!("Matrix is not square. Rows = {}, Columns = {}", self.rows.len(), self.cols);
+        }
+        let mut reduced = self.clone();
+        let mut inverse = Gf2Matrix::identity(self.cols);
+        match reduced.row_reduce_with(Some(&mut inverse)).len() == self.cols {
+            true => Some(inverse),
+            false => None
+        }
+    }
+
+    pub fn multiply(&self, other: &Gf2Matrix) -> Gf2Matrix {
+        if self.cols != other.rows.len() {
+            panic!("Matrix dimensions do not match. Columns = {}, Rows = {}", self.cols, other.rows.len());
+        }
+        let mut product = Gf2Matrix::new(self.rows.len(), other.cols);
+        for (row, product_row) in self.rows.iter().zip(&mut product.rows) {
+            let mut product_slice = product_row.as_bitslice_mut();
+            for k in row.iter_ones() {
+                xor_into(&mut product_slice, other.rows[k].as_bitslice());
+            }
+        }
+        product
+    }
+
+    pub fn multiply_vector(&self, x: &BitVector<u64>) -> BitVector<u64> {
+        if x.capacity() != self.cols {
+            panic!("Vector length does not match. Columns = {}, Length = {}", self.cols, x.capacity());
+        }
+        let mut product = BitVector::with_capacity(self.rows.len(), false);
+        for (index, row) in self.rows.iter().enumerate() {
+            let ones: u32 = row.as_words().iter().zip(x.as_words()).map(|(&left, &right)| (left & right).count_ones()).sum();
+            product.set(index, ones % 2 == 1);
+        }
+        product
+    }
+
+    // every row swap and row addition is repeated on `companion`, which must have as many rows
+    fn row_reduce_with(&mut self, mut companion: Option<&mut Gf2Matrix>) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        for col in 0..self.cols {
+            let pivot_row = pivots.len();
+            let found = match (pivot_row..self.rows.len()).find(|&row| self.rows[row][col]) {
+                Some(found) => found,
+                None => continue
+            };
+            self.rows.swap(pivot_row, found);
+            if let Some(ref mut companion) = companion {
+                companion.rows.swap(pivot_row, found);
+            }
+
+            for row in 0..self.rows.len() {
+                if row != pivot_row && self.rows[row][col] {
+                    add_row(&mut self.rows, row, pivot_row);
+                    if let Some(ref mut companion) = companion {
+                        add_row(&mut companion.rows, row, pivot_row);
+                    }
+                }
+            }
+
+            pivots.push(col);
+            if pivots.len() == self.rows.len() {
+                break;
+            }
+        }
+        pivots
+    }
+}
+
+impl PartialEq for Gf2Matrix {
+    fn eq(&self, other: &Gf2Matrix) -> bool {
+        self.cols == other.cols && self.rows.len() == other.rows.len() &&
+            self.rows.iter().zip(&other.rows).all(|(left, right)| left.as_words() == right.as_words())
+    }
+}
+
+impl Eq for Gf2Matrix {}
+
+impl fmt::Debug for Gf2Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[")?;
+        for row in &self.rows {
+            write!(f, "{} ", row)?;
+        }
+        write!(f, "| {}x{}]", self.rows.len(), self.cols)
+    }
+}
+
+impl<'a> From<&'a BitMatrix<u64>> for Gf2Matrix {
+    fn from(matrix: &'a BitMatrix<u64>) -> Gf2Matrix {
+        let rows = (0..matrix.rows()).map(|row| BitVector::from_vec(matrix.row(row).as_raw_slice().to_vec(), matrix.cols())).collect();
+        Gf2Matrix::from_rows(rows, matrix.cols())
+    }
+}
+
+// adds row `source` to row `target`
+fn add_row(rows: &mut [BitVector<u64>], target: usize, source: usize) {
+    let (target_row, source_row) = if target < source {
+        let (left, right) = rows.split_at_mut(source);
+        (&mut left[target], &right[0])
+    }
+    else {
+        let (left, right) = rows.split_at_mut(target);
+        (&mut right[0], &left[source])
+    };
+    xor_into(&mut target_row.as_bitslice_mut(), source_row.as_bitslice());
+}
+
+#[inline]
+fn xor_into(target: &mut BitSliceMut<u64>, source: BitSlice<u64>) {
+    for (target_word, &source_word) in target.as_raw_mut_slice().iter_mut().zip(source.as_raw_slice()) {
+        *target_word ^= source_word;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BitMatrix,BitVector};
+    use super::Gf2Matrix;
+
+    fn create_matrix(rows: &[&str]) -> Gf2Matrix {
+        let cols = rows[0].len();
+        Gf2Matrix::from_rows(rows.iter().map(|row| row.parse().unwrap()).collect(), cols)
+    }
+
+    #[test]
+    fn test_row_reduce() {
+        let mut matrix = create_matrix(&["0110", "1101", "1011"]);
+        let pivots = matrix.row_reduce();
+        assert_eq!(pivots, [0, 1]);
+        assert_eq!(matrix, create_matrix(&["1011", "0110", "0000"]));
+        assert_eq!(format!("{:?}", matrix), "[1011 0110 0000 | 3x4]");
+    }
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(create_matrix(&["0110", "1101", "1011"]).rank(), 2);
+        assert_eq!(Gf2Matrix::identity(100).rank(), 100);
+        assert_eq!(Gf2Matrix::new(3, 5).rank(), 0);
+    }
+
+    #[test]
+    fn test_solve() {
+        let matrix = create_matrix(&["110", "011", "111"]);
+        let b: BitVector<u64> = "101".parse().unwrap();
+        let x = matrix.solve(&b).unwrap();
+        assert_eq!(x.to_string(), "100");
+        assert_eq!(matrix.multiply_vector(&x).as_words(), b.as_words());
+
+        let singular = create_matrix(&["0110", "1101", "1011"]);
+        assert_eq!(singular.solve(&"011".parse().unwrap()).map(|x| x.to_string()), Some("1000".to_string()));
+        assert!(singular.solve(&"010".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_nullspace() {
+        let matrix = create_matrix(&["0110", "1101", "1011"]);
+        let nullspace = matrix.nullspace();
+        assert_eq!(nullspace.len(), 2);
+        for basis_vector in &nullspace {
+            assert_eq!(matrix.multiply_vector(basis_vector).count_ones(), 0);
+        }
+        assert_eq!(nullspace[0].to_string(), "1110");
+        assert_eq!(nullspace[1].to_string(), "1001");
+
+        assert!(Gf2Matrix::identity(5).nullspace().is_empty());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let matrix = create_matrix(&["110", "011", "111"]);
+        let inverse = matrix.inverse().unwrap();
+        assert_eq!(matrix.multiply(&inverse), Gf2Matrix::identity(3));
+        assert_eq!(inverse.multiply(&matrix), Gf2Matrix::identity(3));
+
+        assert!(create_matrix(&["110", "011", "101"]).inverse().is_none());
+    }
+
+    #[test]
+    fn test_inverse_large() {
+        // an upper triangular matrix with a full diagonal is always invertible
+        let mut matrix = Gf2Matrix::identity(150);
+        for row in 0..150 {
+            for col in row + 1..150 {
+                if (row * 31 + col * 17) % 3 == 0 {
+                    matrix.set(row, col, true);
+                }
+            }
+        }
+        let inverse = matrix.inverse().unwrap();
+        assert_eq!(matrix.multiply(&inverse), Gf2Matrix::identity(150));
+        assert_eq!(matrix.rank(), 150);
+    }
+
+    #[test]
+    fn test_from_bit_matrix() {
+        let mut bit_matrix = BitMatrix::<u64>::new(2, 3);
+        bit_matrix.set(0, 2, true);
+        bit_matrix.set(1, 0, true);
+        let matrix = Gf2Matrix::from(&bit_matrix);
+        assert_eq!(matrix, create_matrix(&["001", "100"]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Row length does not match. Columns = 3, Row = 1, Length = 4")]
+    fn test_from_rows_length_does_not_match() {
+        Gf2Matrix::from_rows(vec!["101".parse().unwrap(), "1010".parse().unwrap()], 3);
+    }
+}
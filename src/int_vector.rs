@@ -0,0 +1,257 @@
+use std::cmp;
+use std::fmt;
+use std::iter::FromIterator;
+
+use BitStorage;
+use BitVector;
+
+/// A vector of unsigned integers that all take `bit_width` bits, packed back to back in a `BitVector`.
+///
+/// Bit `j` of the value at index `i` is bit `i * bit_width + j` of the bit vector, so values may straddle storage
+/// elements.
+#[derive(Clone)]
+pub struct IntVector<S: BitStorage> {
+    bits: BitVector<S>,
+    bit_width: u32,
+    len: usize
+}
+
+impl<S: BitStorage> IntVector<S> {
+    pub fn new(bit_width: u32) -> IntVector<S> {
+        IntVector::with_capacity(0, bit_width)
+    }
+
+    /// Creates `capacity` zero values.
+    pub fn with_capacity(capacity: usize, bit_width: u32) -> IntVector<S> {
+        if bit_width == 0 || bit_width > 64 {
+            panic!("Bit width out of range. Bit width = {}", bit_width);
+        }
+        IntVector {
+            bits: BitVector::with_capacity(capacity * bit_width as usize, false),
+            bit_width,
+            len: capacity
+        }
+    }
+
+    /// Uses the smallest bit width that holds the largest of `values`.
+    pub fn from_slice(values: &[u64]) -> IntVector<S> {
+        let max_value = values.iter().cloned().max().unwrap_or(0);
+        let mut int_vector = IntVector::with_capacity(values.len(), IntVector::<S>::required_bit_width(max_value));
+        for (index, &value) in values.iter().enumerate() {
+            int_vector.set(index, value);
+        }
+        int_vector
+    }
+
+    /// Returns the number of bits needed to store `value`, at least 1.
+    pub fn required_bit_width(value: u64) -> u32 {
+        cmp::max(1, 64 - value.leading_zeros())
+    }
+
+    pub fn bit_width(&self) -> u32 {
+        self.bit_width
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> u64 {
+        self.panic_index_out_of_bounds(index);
+        self.bits.get_bits(index * self.bit_width as usize, self.bit_width as usize)
+    }
+
+    pub fn set(&mut self, index: usize, value: u64) {
+        self.panic_index_out_of_bounds(index);
+        self.panic_value_exceeds_bit_width(value);
+        self.bits.set_bits(index * self.bit_width as usize, self.bit_width as usize, value);
+    }
+
+    pub fn push(&mut self, value: u64) {
+        self.panic_value_exceeds_bit_width(value);
+        self.len += 1;
+        self.bits.resize(self.len * self.bit_width as usize, false);
+        let index = self.len - 1;
+        self.set(index, value);
+    }
+
+    pub fn iter(&self) -> Iter<'_, S> {
+        Iter {
+            int_vector: self,
+            index: 0
+        }
+    }
+
+    pub fn as_bit_vector(&self) -> &BitVector<S> {
+        &self.bits
+    }
+
+    #[inline]
+    fn panic_index_out_of_bounds(&self, index: usize) {
+        if index >= self.len {
+            panic!("Index out of bounds. Length = {}, Index = {}", self.len, index);
+        }
+    }
+
+    #[inline]
+    fn panic_value_exceeds_bit_width(&self, value: u64) {
+        if IntVector::<S>::required_bit_width(value) > self.bit_width {
+            panic!("Value exceeds bit width. Bit width = {}, Value = {}", self.bit_width, value);
+        }
+    }
+}
+
+impl<S: BitStorage> fmt::Debug for IntVector<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<S: BitStorage> PartialEq for IntVector<S> {
+    fn eq(&self, other: &IntVector<S>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<S: BitStorage> Eq for IntVector<S> {}
+
+/// Collects into an `IntVector` with the smallest bit width that holds every value.
+impl<S: BitStorage> FromIterator<u64> for IntVector<S> {
+    fn from_iter<I: IntoIterator<Item = u64>>(iter: I) -> IntVector<S> {
+        let values: Vec<u64> = iter.into_iter().collect();
+        IntVector::from_slice(&values)
+    }
+}
+
+impl<'a, S: BitStorage + 'a> IntoIterator for &'a IntVector<S> {
+    type Item = u64;
+    type IntoIter = Iter<'a, S>;
+
+    fn into_iter(self) -> Iter<'a, S> {
+        self.iter()
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct Iter<'a, S: BitStorage + 'a> {
+    int_vector: &'a IntVector<S>,
+    index: usize
+}
+
+impl<'a, S: BitStorage + 'a> Iterator for Iter<'a, S> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.index == self.int_vector.len {
+            return None;
+        }
+        self.index += 1;
+        Some(self.int_vector.get(self.index - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.int_vector.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, S: BitStorage + 'a> ExactSizeIterator for Iter<'a, S> {}
+
+#[cfg(test)]
+mod tests {
+    use super::IntVector;
+
+    #[test]
+    fn test_get_and_set() {
+        let mut int_vector = IntVector::<u8>::with_capacity(5, 3);
+        int_vector.set(0, 5);
+        int_vector.set(2, 7);
+        int_vector.set(4, 1);
+        assert_eq!(int_vector.get(0), 5);
+        assert_eq!(int_vector.get(1), 0);
+        assert_eq!(int_vector.get(2), 7);
+        assert_eq!(int_vector.get(4), 1);
+        // the value at index 2 spans bits 6 to 8, across the first two elements
+        assert_eq!(int_vector.as_bit_vector().as_words(), [0b11000101, 0b00010001]);
+
+        int_vector.set(2, 2);
+        assert_eq!(int_vector.get(2), 2);
+        assert_eq!(int_vector.get(1), 0);
+        assert_eq!(int_vector.get(3), 0);
+    }
+
+    #[test]
+    fn test_straddling_values() {
+        let values: Vec<u64> = (0..100u64).map(|index| index.wrapping_mul(0x9e37_79b9_7f4a_7c15u64) >> 21).collect();
+        let mut int_vector = IntVector::<u32>::with_capacity(100, 43);
+        for (index, &value) in values.iter().enumerate() {
+            int_vector.set(index, value);
+        }
+        assert_eq!(int_vector.iter().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_full_width() {
+        let mut int_vector = IntVector::<u16>::new(64);
+        int_vector.push(u64::MAX);
+        int_vector.push(0);
+        int_vector.push(0x0123_4567_89ab_cdef);
+        assert_eq!(int_vector.iter().collect::<Vec<_>>(), [u64::MAX, 0, 0x0123_4567_89ab_cdef]);
+        assert_eq!(int_vector.as_bit_vector().capacity(), 192);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut int_vector = IntVector::<u64>::new(10);
+        assert_eq!(int_vector.is_empty(), true);
+        for value in 0..1000 {
+            int_vector.push(value);
+        }
+        assert_eq!(int_vector.len(), 1000);
+        assert_eq!(int_vector.get(999), 999);
+        assert_eq!(int_vector.as_bit_vector().as_words().len(), 157);
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let int_vector = IntVector::<u64>::from_slice(&[3, 17, 0, 9]);
+        assert_eq!(int_vector.bit_width(), 5);
+        assert_eq!(int_vector.iter().collect::<Vec<_>>(), [3, 17, 0, 9]);
+        assert_eq!(format!("{:?}", int_vector), "[3, 17, 0, 9]");
+
+        let int_vector: IntVector<u8> = vec![0, 0].into_iter().collect();
+        assert_eq!(int_vector.bit_width(), 1);
+        assert_eq!(int_vector, IntVector::with_capacity(2, 1));
+    }
+
+    #[test]
+    fn test_required_bit_width() {
+        assert_eq!(IntVector::<u8>::required_bit_width(0), 1);
+        assert_eq!(IntVector::<u8>::required_bit_width(1), 1);
+        assert_eq!(IntVector::<u8>::required_bit_width(255), 8);
+        assert_eq!(IntVector::<u8>::required_bit_width(256), 9);
+        assert_eq!(IntVector::<u8>::required_bit_width(u64::MAX), 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Value exceeds bit width. Bit width = 3, Value = 8")]
+    fn test_value_exceeds_bit_width() {
+        IntVector::<u8>::with_capacity(1, 3).set(0, 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index out of bounds. Length = 2, Index = 2")]
+    fn test_index_out_of_bounds() {
+        IntVector::<u8>::with_capacity(2, 3).get(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bit width out of range. Bit width = 65")]
+    fn test_bit_width_out_of_range() {
+        IntVector::<u8>::new(65);
+    }
+}
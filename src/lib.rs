@@ -1,18 +1,70 @@
-extern crate num;
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(all(test, feature = "serde"))]
+extern crate bincode;
 
 macro_rules! bool_ref {
     ($cond:expr) => (if $cond { &TRUE } else { &FALSE })
 }
 
 mod bit_storage;
+mod bit_order;
 mod bit_vector;
 mod bit_slice;
 mod bit_slice_mut;
+mod bit_set;
+mod bit_array;
+mod small_bit_vector;
+mod file_format;
+mod bit_string;
+mod run_length_encoded;
+mod roaring_bitmap;
+mod ewah_bitmap;
+mod bloom_filter;
+mod blocked_bloom_filter;
+mod counting_bloom_filter;
+mod bit_matrix;
+mod gf2_matrix;
+mod int_vector;
+mod bit_stream;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "mmap")]
+mod mmap_bit_vector;
+#[cfg(feature = "simd")]
+mod simd_storage;
 
 pub use bit_storage::BitStorage;
+pub use bit_order::{BitOrder,Lsb0,Msb0};
 pub use bit_vector::BitVector;
 pub use bit_slice::BitSlice;
 pub use bit_slice_mut::BitSliceMut;
+pub use bit_set::BitSet;
+pub use bit_array::BitArray;
+pub use small_bit_vector::SmallBitVector;
+pub use file_format::FormatError;
+pub use bit_string::ParseBitVectorError;
+pub use run_length_encoded::{RunLengthEncoded,RunLengthError};
+pub use roaring_bitmap::{RoaringBitmap,RoaringFormatError};
+pub use ewah_bitmap::EwahBitmap;
+pub use bloom_filter::{BloomFilter,BloomFilterError};
+pub use blocked_bloom_filter::BlockedBloomFilter;
+pub use counting_bloom_filter::CountingBloomFilter;
+pub use bit_matrix::BitMatrix;
+pub use gf2_matrix::Gf2Matrix;
+pub use int_vector::IntVector;
+pub use bit_stream::{BitReadError,BitReader,BitWriter};
+#[cfg(feature = "mmap")]
+pub use mmap_bit_vector::MmapBitVector;
+#[cfg(feature = "simd")]
+pub use simd_storage::U64x4;
 
 static TRUE: bool = true;
 static FALSE: bool = false;
\ No newline at end of file
@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Index;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use bit_slice::{Iter,IterOnes};
+use file_format::{Crc32,Header,CHECKSUM_SIZE,HEADER_SIZE,NATIVE_ENDIANNESS};
+use BitStorage;
+use BitOrder;
+use Lsb0;
+use BitSlice;
+use FormatError;
+
+use TRUE;
+use FALSE;
+
+/// A read-only bit vector backed by a memory mapped file in the format written by `BitVector::write_to`.
+///
+/// The storage elements are used in place, so the file must have been written on a machine with the same endianness.
+#[derive(Debug)]
+pub struct MmapBitVector<S: BitStorage, O: BitOrder = Lsb0> {
+    mmap: Mmap,
+    capacity: usize,
+    phantom: PhantomData<S>,
+    order: PhantomData<O>
+}
+
+impl<S: BitStorage, O: BitOrder> MmapBitVector<S, O> {
+    /// Maps the file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, for as long as it is mapped.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<MmapBitVector<S, O>, FormatError> {
+        let file = File::open(path)?;
+        MmapBitVector::from_mmap(Mmap::map(&file)?)
+    }
+
+    /// Validates the header and size of an existing mapping, the checksum is only checked by `verify_checksum`.
+    pub fn from_mmap(mmap: Mmap) -> Result<MmapBitVector<S, O>, FormatError> {
+        if mmap.len() < HEADER_SIZE {
+            return Err(FormatError::Truncated { expected: HEADER_SIZE as u64, found: mmap.len() as u64 });
+        }
+        let header = Header::from_bytes::<S, O>(&mmap[..HEADER_SIZE])?;
+        if header.endianness != NATIVE_ENDIANNESS {
+            return Err(FormatError::EndiannessMismatch { expected: NATIVE_ENDIANNESS, found: header.endianness });
+        }
+        let expected = (HEADER_SIZE + Self::compute_payload_size(header.capacity) + CHECKSUM_SIZE) as u64;
+        if (mmap.len() as u64) < expected {
+            return Err(FormatError::Truncated { expected, found: mmap.len() as u64 });
+        }
+        // a mapping made with a non page aligned `MmapOptions::offset` can start anywhere
+        if mmap[HEADER_SIZE..].as_ptr() as usize % mem::align_of::<S>() != 0 {
+            return Err(FormatError::Misaligned { alignment: mem::align_of::<S>() });
+        }
+
+        Ok(MmapBitVector {
+            mmap,
+            capacity: header.capacity,
+            phantom: PhantomData,
+            order: PhantomData
+        })
+    }
+
+    pub fn verify_checksum(&self) -> Result<(), FormatError> {
+        let checksum_start = HEADER_SIZE + Self::compute_payload_size(self.capacity);
+        let mut crc32 = Crc32::new();
+        crc32.update(&self.mmap[..checksum_start]);
+
+        let mut checksum = [0u8; CHECKSUM_SIZE];
+        checksum.copy_from_slice(&self.mmap[checksum_start..checksum_start + CHECKSUM_SIZE]);
+        let checksum = u32::from_le_bytes(checksum);
+        let computed = crc32.finish();
+        if checksum != computed {
+            return Err(FormatError::ChecksumMismatch { expected: checksum, found: computed });
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.as_bitslice().get(index)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.as_bitslice().count_ones()
+    }
+
+    /// Returns the number of set bits before `index`.
+    pub fn rank(&self, index: usize) -> usize {
+        self.as_bitslice().rank(index)
+    }
+
+    pub fn iter_ones(&self) -> IterOnes<'_, S, O> {
+        self.as_bitslice().iter_ones()
+    }
+
+    pub fn as_bitslice(&self) -> BitSlice<'_, S, O> {
+        let pointer = self.mmap[HEADER_SIZE..].as_ptr() as *const S;
+        unsafe { BitSlice::from_pointer(pointer, self.capacity) }
+    }
+
+    pub fn iter(&self) -> Iter<'_, S, O> {
+        self.as_bitslice().iter()
+    }
+
+    #[inline]
+    fn compute_payload_size(capacity: usize) -> usize {
+        capacity.div_ceil(S::storage_size()) * mem::size_of::<S>()
+    }
+}
+
+impl<S: BitStorage, O: BitOrder> Index<usize> for MmapBitVector<S, O> {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &bool {
+        bool_ref!(self.as_bitslice()[index])
+    }
+}
+
+impl<'a, S: BitStorage + 'a, O: BitOrder> IntoIterator for &'a MmapBitVector<S, O> {
+    type Item = bool;
+    type IntoIter = Iter<'a, S, O>;
+
+    fn into_iter(self) -> Iter<'a, S, O> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self,File};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::process;
+
+    use memmap2::MmapOptions;
+
+    use super::super::{BitVector,FormatError,Msb0};
+    use super::MmapBitVector;
+
+    fn write_to_temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("bit-vector-{}-{}", process::id(), name));
+        File::create(&path).unwrap().write_all(bytes).unwrap();
+        path
+    }
+
+    fn create_bytes_u64_200() -> Vec<u8> {
+        let mut vec = BitVector::<u64>::with_capacity(200, false);
+        vec.set(0, true);
+        vec.set(63, true);
+        vec.set(64, true);
+        vec.set(199, true);
+
+        let mut bytes = vec![];
+        vec.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_open() {
+        let path = write_to_temp_file("open", &create_bytes_u64_200());
+        let vec = unsafe { MmapBitVector::<u64>::open(&path).unwrap() };
+
+        assert_eq!(vec.capacity(), 200);
+        assert_eq!(vec.get(0), Some(true));
+        assert_eq!(vec.get(1), Some(false));
+        assert_eq!(vec.get(200), None);
+        assert_eq!(vec[199], true);
+        assert_eq!(vec.count_ones(), 4);
+        assert_eq!(vec.rank(64), 2);
+        assert_eq!(vec.rank(200), 4);
+
+        let ones: Vec<_> = vec.iter_ones().collect();
+        assert_eq!(ones, [0, 63, 64, 199]);
+        assert_eq!(vec.iter().count(), 200);
+        assert!(vec.verify_checksum().is_ok());
+
+        drop(vec);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_msb0() {
+        let mut vec = BitVector::<u16, Msb0>::with_capacity(20, false);
+        vec.set(3, true);
+        vec.set(19, true);
+        let mut bytes = vec![];
+        vec.write_to(&mut bytes).unwrap();
+
+        let path = write_to_temp_file("open-msb0", &bytes);
+        let vec = unsafe { MmapBitVector::<u16, Msb0>::open(&path).unwrap() };
+        assert_eq!(vec.as_bitslice().as_raw_slice(), [0b0001000000000000, 0b0001000000000000]);
+        assert_eq!(vec.count_ones(), 2);
+
+        drop(vec);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_word_size_mismatch() {
+        let path = write_to_temp_file("word-size-mismatch", &create_bytes_u64_200());
+
+        match unsafe { MmapBitVector::<u32>::open(&path) } {
+            Err(FormatError::WordSizeMismatch { expected: 32, found: 64 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_truncated() {
+        let bytes = create_bytes_u64_200();
+        let path = write_to_temp_file("truncated", &bytes[..bytes.len() - 1]);
+
+        match unsafe { MmapBitVector::<u64>::open(&path) } {
+            Err(FormatError::Truncated { expected: 68, found: 67 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_from_mmap_misaligned() {
+        let mut bytes = vec![0];
+        bytes.extend(create_bytes_u64_200());
+        let path = write_to_temp_file("misaligned", &bytes);
+
+        let mmap = unsafe { MmapOptions::new().offset(1).map(&File::open(&path).unwrap()).unwrap() };
+        match MmapBitVector::<u64>::from_mmap(mmap) {
+            Err(FormatError::Misaligned { alignment: 8 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_other_endianness() {
+        let mut bytes = create_bytes_u64_200();
+        bytes[5] ^= 1;
+        let path = write_to_temp_file("other-endianness", &bytes);
+
+        match unsafe { MmapBitVector::<u64>::open(&path) } {
+            Err(FormatError::EndiannessMismatch { .. }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let mut bytes = create_bytes_u64_200();
+        bytes[40] ^= 0b00000001;
+        let path = write_to_temp_file("checksum", &bytes);
+
+        let vec = unsafe { MmapBitVector::<u64>::open(&path).unwrap() };
+        match vec.verify_checksum() {
+            Err(FormatError::ChecksumMismatch { .. }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        drop(vec);
+        fs::remove_file(path).unwrap();
+    }
+}
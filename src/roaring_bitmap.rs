@@ -0,0 +1,964 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::io::{self,Read,Write};
+use std::iter::FromIterator;
+use std::ops::Range;
+use std::slice;
+
+use bit_slice::IterOnes;
+use run_length_encoded;
+use Lsb0;
+use BitVector;
+use BitSlice;
+
+const CONTAINER_SIZE: usize = 1 << 16;
+const BITMAP_WORDS: usize = CONTAINER_SIZE / 64;
+const ARRAY_MAX_LEN: usize = 4096;
+
+// cookies and layout of the portable format, see https://github.com/RoaringBitmap/RoaringFormatSpec
+const SERIAL_COOKIE_NO_RUNS: u32 = 12346;
+const SERIAL_COOKIE: u32 = 12347;
+const NO_OFFSET_THRESHOLD: usize = 4;
+
+/// A compressed set of `u32` values.
+///
+/// The values are partitioned by their high 16 bits into containers, each holding its low 16 bits as a sorted array,
+/// a 65536 bit `BitVector<u64>` or a list of runs depending on its cardinality. `write_to` and `read_from` use the
+/// portable Roaring format shared with the C, Java and Go implementations.
+#[derive(Clone,Default)]
+pub struct RoaringBitmap {
+    containers: Vec<(u16, Container)>
+}
+
+impl RoaringBitmap {
+    pub fn new() -> RoaringBitmap {
+        RoaringBitmap {
+            containers: Vec::new()
+        }
+    }
+
+    pub fn insert(&mut self, value: u32) -> bool {
+        let (key, low) = split(value);
+        match self.find_container(key) {
+            Ok(index) => self.containers[index].1.insert(low),
+            Err(index) => {
+                self.containers.insert(index, (key, Container::Array(vec![low])));
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, value: u32) -> bool {
+        let (key, low) = split(value);
+        match self.find_container(key) {
+            Ok(index) => {
+                let removed = self.containers[index].1.remove(low);
+                if self.containers[index].1.len() == 0 {
+                    self.containers.remove(index);
+                }
+                removed
+            },
+            Err(_) => false
+        }
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let (key, low) = split(value);
+        match self.find_container(key) {
+            Ok(index) => self.containers[index].1.contains(low),
+            Err(_) => false
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.containers.iter().map(|(_, container)| container.len() as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.containers.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            containers: self.containers.iter(),
+            current: None
+        }
+    }
+
+    pub fn union(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOperation::Union)
+    }
+
+    pub fn intersection(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOperation::Intersection)
+    }
+
+    pub fn difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOperation::Difference)
+    }
+
+    pub fn symmetric_difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOperation::SymmetricDifference)
+    }
+
+    pub fn is_subset(&self, other: &RoaringBitmap) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub fn is_superset(&self, other: &RoaringBitmap) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &RoaringBitmap) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    /// Converts every container to whichever of the array, bitmap and run representations is smallest.
+    ///
+    /// Containers built by `insert` only switch between arrays and bitmaps, the results of set operations are
+    /// already optimized.
+    pub fn run_optimize(&mut self) {
+        for &mut (_, ref mut container) in &mut self.containers {
+            let values: Vec<u16> = container.iter().collect();
+            *container = Container::from_array(values).unwrap();
+        }
+    }
+
+    pub fn serialized_size(&self) -> usize {
+        let header_size = self.header_size();
+        header_size + self.containers.iter().map(|(_, container)| container.serialized_size()).sum::<usize>()
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let size = self.containers.len();
+        if self.has_runs() {
+            writer.write_all(&(SERIAL_COOKIE | (((size - 1) as u32) << 16)).to_le_bytes())?;
+            let mut run_flags = vec![0u8; size.div_ceil(8)];
+            for (index, (_, container)) in self.containers.iter().enumerate() {
+                if let Container::Run(_) = *container {
+                    run_flags[index / 8] |= 1 << (index % 8);
+                }
+            }
+            writer.write_all(&run_flags)?;
+        }
+        else {
+            writer.write_all(&SERIAL_COOKIE_NO_RUNS.to_le_bytes())?;
+            writer.write_all(&(size as u32).to_le_bytes())?;
+        }
+
+        for &(key, ref container) in &self.containers {
+            writer.write_all(&key.to_le_bytes())?;
+            writer.write_all(&((container.len() - 1) as u16).to_le_bytes())?;
+        }
+
+        if self.has_offsets() {
+            let mut offset = self.header_size();
+            for (_, container) in &self.containers {
+                writer.write_all(&(offset as u32).to_le_bytes())?;
+                offset += container.serialized_size();
+            }
+        }
+
+        for (_, container) in &self.containers {
+            container.write_to(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> Result<RoaringBitmap, RoaringFormatError> {
+        let cookie = read_u32(&mut reader)?;
+        let (size, run_flags) = if cookie & 0xffff == SERIAL_COOKIE {
+            let size = (cookie >> 16) as usize + 1;
+            let mut run_flags = vec![0u8; size.div_ceil(8)];
+            reader.read_exact(&mut run_flags)?;
+            (size, Some(run_flags))
+        }
+        else if cookie == SERIAL_COOKIE_NO_RUNS {
+            let size = read_u32(&mut reader)?;
+            if size as usize > CONTAINER_SIZE {
+                return Err(RoaringFormatError::InvalidContainerCount(size));
+            }
+            (size as usize, None)
+        }
+        else {
+            return Err(RoaringFormatError::InvalidCookie(cookie));
+        };
+
+        let mut descriptions = Vec::with_capacity(size);
+        for _ in 0..size {
+            let key = read_u16(&mut reader)?;
+            let len = read_u16(&mut reader)? as usize + 1;
+            descriptions.push((key, len));
+        }
+
+        if run_flags.is_none() || size >= NO_OFFSET_THRESHOLD {
+            let mut offsets = vec![0u8; 4 * size];
+            reader.read_exact(&mut offsets)?;
+        }
+
+        let mut containers: Vec<(u16, Container)> = Vec::with_capacity(size);
+        for (index, (key, len)) in descriptions.into_iter().enumerate() {
+            let is_run = run_flags.as_ref().is_some_and(|run_flags| run_flags[index / 8] & (1 << (index % 8)) != 0);
+            let container = Container::read_from(&mut reader, len, is_run)?;
+            let ascending = containers.last().is_none_or(|&(last_key, _)| last_key < key);
+            if !ascending || container.as_ref().is_none_or(|container| container.len() != len) {
+                return Err(RoaringFormatError::InvalidContainer { index });
+            }
+            containers.push((key, container.unwrap()));
+        }
+        Ok(RoaringBitmap {
+            containers
+        })
+    }
+
+    #[inline]
+    fn find_container(&self, key: u16) -> Result<usize, usize> {
+        self.containers.binary_search_by_key(&key, |&(key, _)| key)
+    }
+
+    // walks the containers of both bitmaps in key order, a container without a counterpart is combined with an empty one
+    fn combine(&self, other: &RoaringBitmap, operation: SetOperation) -> RoaringBitmap {
+        let mut containers = Vec::new();
+        let (mut left, mut right) = (self.containers.iter().peekable(), other.containers.iter().peekable());
+        loop {
+            let ordering = match (left.peek(), right.peek()) {
+                (Some(&&(left_key, _)), Some(&&(right_key, _))) => left_key.cmp(&right_key),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break
+            };
+            match ordering {
+                Ordering::Less => {
+                    let &(key, ref container) = left.next().unwrap();
+                    if operation.keeps(true, false) {
+                        containers.push((key, container.clone()));
+                    }
+                },
+                Ordering::Greater => {
+                    let &(key, ref container) = right.next().unwrap();
+                    if operation.keeps(false, true) {
+                        containers.push((key, container.clone()));
+                    }
+                },
+                Ordering::Equal => {
+                    let &(key, ref left_container) = left.next().unwrap();
+                    let (_, right_container) = right.next().unwrap();
+                    if let Some(container) = Container::combine(left_container, right_container, operation) {
+                        containers.push((key, container));
+                    }
+                }
+            }
+        }
+        RoaringBitmap {
+            containers
+        }
+    }
+
+    #[inline]
+    fn has_runs(&self) -> bool {
+        self.containers.iter().any(|(_, container)| matches!(*container, Container::Run(_)))
+    }
+
+    #[inline]
+    fn has_offsets(&self) -> bool {
+        !self.has_runs() || self.containers.len() >= NO_OFFSET_THRESHOLD
+    }
+
+    fn header_size(&self) -> usize {
+        let size = self.containers.len();
+        let cookie_size = if self.has_runs() { 4 + size.div_ceil(8) } else { 8 };
+        let offsets_size = if self.has_offsets() { 4 * size } else { 0 };
+        cookie_size + 4 * size + offsets_size
+    }
+}
+
+impl PartialEq for RoaringBitmap {
+    fn eq(&self, other: &RoaringBitmap) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for RoaringBitmap {}
+
+impl fmt::Debug for RoaringBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<u32> for RoaringBitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> RoaringBitmap {
+        let mut roaring_bitmap = RoaringBitmap::new();
+        roaring_bitmap.extend(iter);
+        roaring_bitmap
+    }
+}
+
+impl Extend<u32> for RoaringBitmap {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a RoaringBitmap {
+    type Item = u32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+#[derive(Clone,Copy)]
+enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference
+}
+
+impl SetOperation {
+    #[inline]
+    fn keeps(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            SetOperation::Union => in_left || in_right,
+            SetOperation::Intersection => in_left && in_right,
+            SetOperation::Difference => in_left && !in_right,
+            SetOperation::SymmetricDifference => in_left != in_right
+        }
+    }
+
+    #[inline]
+    fn combine_words(self, left: u64, right: u64) -> u64 {
+        match self {
+            SetOperation::Union => left | right,
+            SetOperation::Intersection => left & right,
+            SetOperation::Difference => left & !right,
+            SetOperation::SymmetricDifference => left ^ right
+        }
+    }
+}
+
+// runs are stored as their start and their length minus one, like in the serialized format
+#[derive(Clone)]
+enum Container {
+    Array(Vec<u16>),
+    Bitmap { bits: BitVector<u64>, len: usize },
+    Run(Vec<(u16, u16)>)
+}
+
+impl Container {
+    // picks the representation with the smallest serialized size, runs only win when they are strictly smaller
+    fn from_array(values: Vec<u16>) -> Option<Container> {
+        if values.is_empty() {
+            return None;
+        }
+        let run_count = 1 + values.windows(2).filter(|pair| pair[1] != pair[0] + 1).count();
+        Some(if run_serialized_size(run_count) < dense_serialized_size(values.len()) {
+            Container::Run(collect_runs(values.iter().cloned()))
+        }
+        else if values.len() <= ARRAY_MAX_LEN {
+            Container::Array(values)
+        }
+        else {
+            let mut bits = BitVector::with_capacity(CONTAINER_SIZE, false);
+            for &value in &values {
+                bits.set(value as usize, true);
+            }
+            Container::Bitmap { bits, len: values.len() }
+        })
+    }
+
+    fn from_words(words: Vec<u64>) -> Option<Container> {
+        let len: usize = words.iter().map(|word| word.count_ones() as usize).sum();
+        if len == 0 {
+            return None;
+        }
+        // a run starts at every set bit whose preceding bit is unset
+        let mut run_count = 0;
+        let mut previous_top_bit = 0;
+        for &word in &words {
+            run_count += (word & !((word << 1) | previous_top_bit)).count_ones() as usize;
+            previous_top_bit = word >> 63;
+        }
+        Some(if run_serialized_size(run_count) < dense_serialized_size(len) {
+            Container::Run(collect_runs(ones_in_words(&words)))
+        }
+        else if len <= ARRAY_MAX_LEN {
+            Container::Array(ones_in_words(&words).collect())
+        }
+        else {
+            Container::Bitmap { bits: BitVector::from_vec(words, CONTAINER_SIZE), len }
+        })
+    }
+
+    fn combine(left: &Container, right: &Container, operation: SetOperation) -> Option<Container> {
+        match (left, right, operation) {
+            (Container::Array(left), Container::Array(right), _) => {
+                Container::from_array(merge_sorted(left, right, operation))
+            },
+            (Container::Array(left), right, SetOperation::Intersection) |
+            (right, Container::Array(left), SetOperation::Intersection) => {
+                Container::from_array(left.iter().cloned().filter(|&value| right.contains(value)).collect())
+            },
+            (Container::Array(left), right, SetOperation::Difference) => {
+                Container::from_array(left.iter().cloned().filter(|&value| !right.contains(value)).collect())
+            },
+            (left, right, _) => {
+                let mut words = left.to_words();
+                for (word, right_word) in words.iter_mut().zip(right.to_words()) {
+                    *word = operation.combine_words(*word, right_word);
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Container::Array(ref values) => values.len(),
+            Container::Bitmap { len, .. } => len,
+            Container::Run(ref runs) => runs.iter().map(|&(_, length)| length as usize + 1).sum()
+        }
+    }
+
+    fn contains(&self, low: u16) -> bool {
+        match *self {
+            Container::Array(ref values) => values.binary_search(&low).is_ok(),
+            Container::Bitmap { ref bits, .. } => bits[low as usize],
+            Container::Run(ref runs) => match runs.binary_search_by_key(&low, |&(start, _)| start) {
+                Ok(_) => true,
+                Err(0) => false,
+                Err(index) => {
+                    let (start, length) = runs[index - 1];
+                    low as u32 <= start as u32 + length as u32
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, low: u16) -> bool {
+        self.make_dense();
+        match *self {
+            Container::Array(ref mut values) => match values.binary_search(&low) {
+                Ok(_) => return false,
+                Err(index) => values.insert(index, low)
+            },
+            Container::Bitmap { ref mut bits, ref mut len } => {
+                if bits[low as usize] {
+                    return false;
+                }
+                bits.set(low as usize, true);
+                *len += 1;
+            },
+            Container::Run(_) => unreachable!()
+        }
+        if self.len() > ARRAY_MAX_LEN {
+            if let Container::Array(ref values) = *self {
+                let mut bits = BitVector::with_capacity(CONTAINER_SIZE, false);
+                for &value in values {
+                    bits.set(value as usize, true);
+                }
+                *self = Container::Bitmap { bits, len: values.len() };
+            }
+        }
+        true
+    }
+
+    fn remove(&mut self, low: u16) -> bool {
+        if !self.contains(low) {
+            return false;
+        }
+        self.make_dense();
+        match *self {
+            Container::Array(ref mut values) => {
+                let index = values.binary_search(&low).unwrap();
+                values.remove(index);
+            },
+            Container::Bitmap { ref mut bits, ref mut len } => {
+                bits.set(low as usize, false);
+                *len -= 1;
+            },
+            Container::Run(_) => unreachable!()
+        }
+        if let Container::Bitmap { ref bits, len } = *self {
+            if len <= ARRAY_MAX_LEN {
+                let values = bits.iter_ones().map(|value| value as u16).collect();
+                *self = Container::Array(values);
+            }
+        }
+        true
+    }
+
+    // turns a run container into an array or bitmap, so that it can be changed a value at a time
+    fn make_dense(&mut self) {
+        if let Container::Run(_) = *self {
+            let len = self.len();
+            *self = if len <= ARRAY_MAX_LEN {
+                Container::Array(self.iter().collect())
+            }
+            else {
+                Container::Bitmap { bits: BitVector::from_vec(self.to_words(), CONTAINER_SIZE), len }
+            };
+        }
+    }
+
+    fn iter(&self) -> ContainerIter<'_> {
+        match *self {
+            Container::Array(ref values) => ContainerIter::Array(values.iter()),
+            Container::Bitmap { ref bits, .. } => ContainerIter::Bitmap(bits.iter_ones()),
+            Container::Run(ref runs) => ContainerIter::Run(runs.iter(), 0..0)
+        }
+    }
+
+    fn to_words(&self) -> Vec<u64> {
+        match *self {
+            Container::Bitmap { ref bits, .. } => bits.as_words().to_vec(),
+            Container::Array(ref values) => {
+                let mut words = vec![0u64; BITMAP_WORDS];
+                for &value in values {
+                    words[value as usize / 64] |= 1 << (value % 64);
+                }
+                words
+            },
+            Container::Run(ref runs) => {
+                let mut words = vec![0u64; BITMAP_WORDS];
+                for &(start, length) in runs {
+                    run_length_encoded::set_range::<u64, Lsb0>(&mut words, start as usize, start as usize + length as usize + 1);
+                }
+                words
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        match *self {
+            Container::Array(ref values) => dense_serialized_size(values.len()),
+            Container::Bitmap { len, .. } => dense_serialized_size(len),
+            Container::Run(ref runs) => run_serialized_size(runs.len())
+        }
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match *self {
+            Container::Array(ref values) => {
+                for &value in values {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            },
+            Container::Bitmap { ref bits, .. } => {
+                for &word in bits.as_words() {
+                    writer.write_all(&word.to_le_bytes())?;
+                }
+            },
+            Container::Run(ref runs) => {
+                writer.write_all(&(runs.len() as u16).to_le_bytes())?;
+                for &(start, length) in runs {
+                    writer.write_all(&start.to_le_bytes())?;
+                    writer.write_all(&length.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // returns None when the values are not sorted or the runs overlap
+    fn read_from<R: Read>(reader: &mut R, len: usize, is_run: bool) -> io::Result<Option<Container>> {
+        if is_run {
+            let run_count = read_u16(reader)? as usize;
+            let mut runs = Vec::with_capacity(run_count);
+            let mut next_start = 0;
+            for _ in 0..run_count {
+                let (start, length) = (read_u16(reader)?, read_u16(reader)?);
+                if (start as u32) < next_start || start as u32 + length as u32 >= CONTAINER_SIZE as u32 {
+                    return Ok(None);
+                }
+                next_start = start as u32 + length as u32 + 2;
+                runs.push((start, length));
+            }
+            Ok(if runs.is_empty() { None } else { Some(Container::Run(runs)) })
+        }
+        else if len <= ARRAY_MAX_LEN {
+            let mut values = Vec::with_capacity(len);
+            for _ in 0..len {
+                values.push(read_u16(reader)?);
+            }
+            let sorted = values.windows(2).all(|pair| pair[0] < pair[1]);
+            Ok(if sorted { Some(Container::Array(values)) } else { None })
+        }
+        else {
+            let mut words = Vec::with_capacity(BITMAP_WORDS);
+            for _ in 0..BITMAP_WORDS {
+                let mut word = [0u8; 8];
+                reader.read_exact(&mut word)?;
+                words.push(u64::from_le_bytes(word));
+            }
+            let len = words.iter().map(|word| word.count_ones() as usize).sum();
+            Ok(Some(Container::Bitmap { bits: BitVector::from_vec(words, CONTAINER_SIZE), len }))
+        }
+    }
+}
+
+enum ContainerIter<'a> {
+    Array(slice::Iter<'a, u16>),
+    Bitmap(IterOnes<'a, u64>),
+    Run(slice::Iter<'a, (u16, u16)>, Range<u32>)
+}
+
+impl<'a> Iterator for ContainerIter<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        match *self {
+            ContainerIter::Array(ref mut values) => values.next().cloned(),
+            ContainerIter::Bitmap(ref mut ones) => ones.next().map(|value| value as u16),
+            ContainerIter::Run(ref mut runs, ref mut current) => {
+                if current.start == current.end {
+                    let &(start, length) = runs.next()?;
+                    *current = start as u32..start as u32 + length as u32 + 1;
+                }
+                current.next().map(|value| value as u16)
+            }
+        }
+    }
+}
+
+pub struct Iter<'a> {
+    containers: slice::Iter<'a, (u16, Container)>,
+    current: Option<(u32, ContainerIter<'a>)>
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            if let Some((high, ref mut values)) = self.current {
+                if let Some(low) = values.next() {
+                    return Some(high | low as u32);
+                }
+            }
+            let &(key, ref container) = self.containers.next()?;
+            self.current = Some(((key as u32) << 16, container.iter()));
+        }
+    }
+}
+
+#[inline]
+fn split(value: u32) -> (u16, u16) {
+    ((value >> 16) as u16, value as u16)
+}
+
+#[inline]
+fn dense_serialized_size(len: usize) -> usize {
+    if len <= ARRAY_MAX_LEN { 2 * len } else { BITMAP_WORDS * 8 }
+}
+
+#[inline]
+fn run_serialized_size(run_count: usize) -> usize {
+    2 + 4 * run_count
+}
+
+fn ones_in_words(words: &[u64]) -> impl Iterator<Item = u16> + '_ {
+    BitSlice::<u64>::from_slice(words).iter_ones().map(|value| value as u16)
+}
+
+fn collect_runs<I: Iterator<Item = u16>>(values: I) -> Vec<(u16, u16)> {
+    let mut runs: Vec<(u16, u16)> = Vec::new();
+    for value in values {
+        match runs.last_mut() {
+            Some(&mut (start, ref mut length)) if start as u32 + *length as u32 + 1 == value as u32 => *length += 1,
+            _ => runs.push((value, 0))
+        }
+    }
+    runs
+}
+
+fn merge_sorted(left: &[u16], right: &[u16], operation: SetOperation) -> Vec<u16> {
+    let mut merged = Vec::new();
+    let (mut left_index, mut right_index) = (0, 0);
+    while left_index < left.len() || right_index < right.len() {
+        let ordering = match (left.get(left_index), right.get(right_index)) {
+            (Some(left_value), Some(right_value)) => left_value.cmp(right_value),
+            (Some(_), None) => Ordering::Less,
+            _ => Ordering::Greater
+        };
+        let (value, in_left, in_right) = match ordering {
+            Ordering::Less => (left[left_index], true, false),
+            Ordering::Greater => (right[right_index], false, true),
+            Ordering::Equal => (left[left_index], true, true)
+        };
+        if in_left {
+            left_index += 1;
+        }
+        if in_right {
+            right_index += 1;
+        }
+        if operation.keeps(in_left, in_right) {
+            merged.push(value);
+        }
+    }
+    merged
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RoaringFormatError {
+    Io(io::Error),
+    InvalidCookie(u32),
+    InvalidContainerCount(u32),
+    InvalidContainer { index: usize }
+}
+
+impl fmt::Display for RoaringFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RoaringFormatError::Io(ref error) => write!(f, "I/O error: {}", error),
+            RoaringFormatError::InvalidCookie(cookie) => write!(f, "Invalid cookie. Cookie = {}", cookie),
+            RoaringFormatError::InvalidContainerCount(count) => write!(f, "Invalid container count. Count = {}", count),
+            RoaringFormatError::InvalidContainer { index } => write!(f, "Invalid container. Index = {}", index)
+        }
+    }
+}
+
+impl Error for RoaringFormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            RoaringFormatError::Io(ref error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for RoaringFormatError {
+    fn from(error: io::Error) -> RoaringFormatError {
+        RoaringFormatError::Io(error)
+    }
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Container,RoaringBitmap,RoaringFormatError};
+
+    fn container_kinds(roaring_bitmap: &RoaringBitmap) -> Vec<&'static str> {
+        roaring_bitmap.containers.iter().map(|(_, container)| match *container {
+            Container::Array(_) => "array",
+            Container::Bitmap { .. } => "bitmap",
+            Container::Run(_) => "run"
+        }).collect()
+    }
+
+    fn create_mixed_roaring_bitmap() -> RoaringBitmap {
+        let mut roaring_bitmap: RoaringBitmap = (0..10000).map(|value| value * 3).collect();
+        roaring_bitmap.extend(vec![1 << 16, (1 << 16) + 5, 3 << 16]);
+        roaring_bitmap.extend((5 << 16)..(5 << 16) + 20000);
+        roaring_bitmap.insert(u32::MAX);
+        roaring_bitmap
+    }
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut roaring_bitmap = RoaringBitmap::new();
+
+        assert_eq!(roaring_bitmap.insert(3), true);
+        assert_eq!(roaring_bitmap.insert(3), false);
+        assert_eq!(roaring_bitmap.insert(70000), true);
+        assert_eq!(roaring_bitmap.insert(u32::MAX), true);
+
+        assert_eq!(roaring_bitmap.contains(3), true);
+        assert_eq!(roaring_bitmap.contains(70000), true);
+        assert_eq!(roaring_bitmap.contains(u32::MAX), true);
+        assert_eq!(roaring_bitmap.contains(4), false);
+        assert_eq!(roaring_bitmap.contains(65539), false);
+        assert_eq!(roaring_bitmap.len(), 3);
+
+        assert_eq!(roaring_bitmap.remove(70000), true);
+        assert_eq!(roaring_bitmap.remove(70000), false);
+        assert_eq!(roaring_bitmap.len(), 2);
+        assert_eq!(container_kinds(&roaring_bitmap), ["array", "array"]);
+    }
+
+    #[test]
+    fn test_array_bitmap_switch() {
+        let mut roaring_bitmap: RoaringBitmap = (0..4096).map(|value| value * 2).collect();
+        assert_eq!(container_kinds(&roaring_bitmap), ["array"]);
+
+        roaring_bitmap.insert(1);
+        assert_eq!(container_kinds(&roaring_bitmap), ["bitmap"]);
+        assert_eq!(roaring_bitmap.len(), 4097);
+        assert_eq!(roaring_bitmap.contains(1), true);
+        assert_eq!(roaring_bitmap.contains(3), false);
+
+        roaring_bitmap.remove(8190);
+        assert_eq!(container_kinds(&roaring_bitmap), ["array"]);
+        assert_eq!(roaring_bitmap.len(), 4096);
+        assert_eq!(roaring_bitmap.contains(1), true);
+    }
+
+    #[test]
+    fn test_run_optimize() {
+        let mut roaring_bitmap: RoaringBitmap = (100..60000).chain(vec![70000, 70002]).collect();
+        assert_eq!(container_kinds(&roaring_bitmap), ["bitmap", "array"]);
+
+        roaring_bitmap.run_optimize();
+        assert_eq!(container_kinds(&roaring_bitmap), ["run", "array"]);
+        assert_eq!(roaring_bitmap.len(), 59902);
+        assert_eq!(roaring_bitmap.contains(99), false);
+        assert_eq!(roaring_bitmap.contains(100), true);
+        assert_eq!(roaring_bitmap.contains(59999), true);
+        assert_eq!(roaring_bitmap.contains(60000), false);
+
+        roaring_bitmap.insert(60001);
+        roaring_bitmap.remove(100);
+        assert_eq!(container_kinds(&roaring_bitmap), ["bitmap", "array"]);
+        assert_eq!(roaring_bitmap.len(), 59902);
+    }
+
+    #[test]
+    fn test_iter() {
+        let roaring_bitmap: RoaringBitmap = vec![u32::MAX, 70000, 3, 65536, 3].into_iter().collect();
+        let values: Vec<_> = roaring_bitmap.iter().collect();
+        assert_eq!(values, [3, 65536, 70000, u32::MAX]);
+
+        let roaring_bitmap = create_mixed_roaring_bitmap();
+        let values: Vec<_> = (&roaring_bitmap).into_iter().collect();
+        assert_eq!(values.len() as u64, roaring_bitmap.len());
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let left = create_mixed_roaring_bitmap();
+        let mut right: RoaringBitmap = (0..20000).map(|value| value * 2).collect();
+        right.extend((5 << 16) + 10000..(5 << 16) + 40000);
+        right.insert(3 << 16);
+        right.run_optimize();
+
+        let expected = |keep: fn(bool, bool) -> bool| -> Vec<u32> {
+            let mut values: Vec<u32> = left.iter().chain(right.iter()).collect();
+            values.sort();
+            values.dedup();
+            values.into_iter().filter(|&value| keep(left.contains(value), right.contains(value))).collect()
+        };
+
+        let union: Vec<_> = left.union(&right).iter().collect();
+        assert_eq!(union, expected(|l, r| l || r));
+        let intersection: Vec<_> = left.intersection(&right).iter().collect();
+        assert_eq!(intersection, expected(|l, r| l && r));
+        let difference: Vec<_> = left.difference(&right).iter().collect();
+        assert_eq!(difference, expected(|l, r| l && !r));
+        let symmetric_difference: Vec<_> = left.symmetric_difference(&right).iter().collect();
+        assert_eq!(symmetric_difference, expected(|l, r| l != r));
+
+        assert_eq!(left.union(&right).len(), union.len() as u64);
+        assert!(left.intersection(&left).eq(&left));
+        assert!(left.difference(&left).is_empty());
+    }
+
+    #[test]
+    fn test_subset_superset_disjoint() {
+        let small: RoaringBitmap = vec![1, 70000].into_iter().collect();
+        let large: RoaringBitmap = (0..100000).collect();
+        let other: RoaringBitmap = vec![200000].into_iter().collect();
+
+        assert!(small.is_subset(&large));
+        assert!(!large.is_subset(&small));
+        assert!(large.is_superset(&small));
+        assert!(small.is_disjoint(&other));
+        assert!(!small.is_disjoint(&large));
+    }
+
+    #[test]
+    fn test_eq_ignores_representation() {
+        let mut left: RoaringBitmap = (0..1000).collect();
+        let right = left.clone();
+        left.run_optimize();
+
+        assert_eq!(container_kinds(&left), ["run"]);
+        assert_eq!(container_kinds(&right), ["array"]);
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_write_to_without_runs() {
+        let roaring_bitmap: RoaringBitmap = vec![1, 2, 3].into_iter().collect();
+        let mut bytes = vec![];
+        roaring_bitmap.write_to(&mut bytes).unwrap();
+
+        assert_eq!(bytes, [0x3a, 0x30, 0, 0, 1, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 1, 0, 2, 0, 3, 0]);
+        assert_eq!(bytes.len(), roaring_bitmap.serialized_size());
+    }
+
+    #[test]
+    fn test_write_to_with_runs() {
+        let mut roaring_bitmap: RoaringBitmap = (0..10).collect();
+        roaring_bitmap.run_optimize();
+        let mut bytes = vec![];
+        roaring_bitmap.write_to(&mut bytes).unwrap();
+
+        assert_eq!(bytes, [0x3b, 0x30, 0, 0, 1, 0, 0, 9, 0, 1, 0, 0, 0, 9, 0]);
+        assert_eq!(bytes.len(), roaring_bitmap.serialized_size());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut roaring_bitmap = create_mixed_roaring_bitmap();
+        for run_optimize in [false, true] {
+            if run_optimize {
+                roaring_bitmap.run_optimize();
+            }
+            let mut bytes = vec![];
+            roaring_bitmap.write_to(&mut bytes).unwrap();
+            assert_eq!(bytes.len(), roaring_bitmap.serialized_size());
+
+            let read = RoaringBitmap::read_from(&bytes[..]).unwrap();
+            assert_eq!(read, roaring_bitmap);
+            assert_eq!(container_kinds(&read), container_kinds(&roaring_bitmap));
+        }
+        assert_eq!(container_kinds(&roaring_bitmap), ["bitmap", "array", "array", "run", "array"]);
+
+        let empty = RoaringBitmap::new();
+        let mut bytes = vec![];
+        empty.write_to(&mut bytes).unwrap();
+        assert_eq!(RoaringBitmap::read_from(&bytes[..]).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        match RoaringBitmap::read_from(&[1u8, 2, 3, 4][..]) {
+            Err(RoaringFormatError::InvalidCookie(0x04030201)) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        let unsorted = [0x3a, 0x30, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 12, 0, 0, 0, 2, 0, 1, 0];
+        match RoaringBitmap::read_from(&unsorted[..]) {
+            Err(RoaringFormatError::InvalidContainer { index: 0 }) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+
+        let truncated = [0x3a, 0x30, 0, 0, 1, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 1, 0, 2, 0];
+        match RoaringBitmap::read_from(&truncated[..]) {
+            Err(RoaringFormatError::Io(_)) => {},
+            other => panic!("Unexpected result: {:?}", other)
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::iter;
+
+use BitStorage;
+use BitOrder;
+use BitVector;
+
+/// A bit vector compressed into runs of equal bits.
+///
+/// The encoding is one byte holding the value of the first run, followed by the length of every run as an unsigned
+/// LEB128 varint. Runs alternate between ones and zeros, so only the first value is stored.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct RunLengthEncoded {
+    bytes: Vec<u8>,
+    len: usize
+}
+
+impl RunLengthEncoded {
+    pub fn encode<S: BitStorage, O: BitOrder>(bit_vector: &BitVector<S, O>) -> RunLengthEncoded {
+        let mut runs = bit_vector.iter_runs().peekable();
+        let first_value = runs.peek().is_some_and(|&(value, _)| value);
+
+        let mut bytes = vec![first_value as u8];
+        for (_, length) in runs {
+            write_varint(&mut bytes, length as u64);
+        }
+        RunLengthEncoded {
+            bytes,
+            len: bit_vector.capacity()
+        }
+    }
+
+    pub fn decode<S: BitStorage, O: BitOrder>(&self) -> BitVector<S, O> {
+        let mut bit_vector = BitVector::with_capacity(self.len, false);
+        {
+            let mut slice = bit_vector.as_bitslice_mut();
+            let data = slice.as_raw_mut_slice();
+            let mut index = 0;
+            for (value, length) in self.runs() {
+                if value {
+                    set_range::<S, O>(data, index, index + length);
+                }
+                index += length;
+            }
+        }
+        bit_vector
+    }
+
+    /// Validates encoded bytes, rejecting them when they decode to more than `max_len` bits so that untrusted input
+    /// cannot make `decode` allocate an arbitrary amount of memory.
+    pub fn from_bytes(bytes: Vec<u8>, max_len: usize) -> Result<RunLengthEncoded, RunLengthError> {
+        match bytes.first() {
+            None => return Err(RunLengthError::Empty),
+            Some(&value) if value > 1 => return Err(RunLengthError::InvalidRunValue(value)),
+            _ => {}
+        }
+
+        let mut len: usize = 0;
+        let mut position = 1;
+        while position < bytes.len() {
+            let start = position;
+            let length = match read_varint(&bytes, &mut position) {
+                Some(length) if length > 0 && length <= usize::MAX as u64 => length as usize,
+                _ => return Err(RunLengthError::InvalidRunLength { position: start })
+            };
+            len = match len.checked_add(length) {
+                Some(len) if len <= max_len => len,
+                _ => return Err(RunLengthError::LengthExceeded { position: start, max_len })
+            };
+        }
+        Ok(RunLengthEncoded {
+            bytes,
+            len
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Returns the number of bits in the decoded bit vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // only called on validated bytes
+    fn runs(&self) -> impl Iterator<Item = (bool, usize)> + '_ {
+        let mut value = self.bytes[0] == 1;
+        let mut position = 1;
+        iter::from_fn(move || {
+            let length = read_varint(&self.bytes, &mut position)?;
+            let run = (value, length as usize);
+            value = !value;
+            Some(run)
+        })
+    }
+}
+
+#[derive(Clone,Debug,PartialEq,Eq)]
+#[non_exhaustive]
+pub enum RunLengthError {
+    Empty,
+    InvalidRunValue(u8),
+    InvalidRunLength { position: usize },
+    LengthExceeded { position: usize, max_len: usize }
+}
+
+impl fmt::Display for RunLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RunLengthError::Empty => write!(f, "Encoded bytes are empty"),
+            RunLengthError::InvalidRunValue(value) => write!(f, "Invalid value of the first run. Value = {}", value),
+            RunLengthError::InvalidRunLength { position } => write!(f, "Invalid run length. Position = {}", position),
+            RunLengthError::LengthExceeded { position, max_len } => write!(f, "Length exceeds maximum. Position = {}, Maximum length = {}", position, max_len)
+        }
+    }
+}
+
+impl Error for RunLengthError {}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+// returns None when the varint is truncated or does not fit in a u64
+fn read_varint(bytes: &[u8], position: &mut usize) -> Option<u64> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*position)?;
+        *position += 1;
+        let bits = (byte & 0x7f) as u64;
+        if shift == 63 && bits > 1 || shift > 63 {
+            return None;
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+    }
+}
+
+// sets the bits in `start..end` a storage element at a time
+pub(crate) fn set_range<S: BitStorage, O: BitOrder>(data: &mut [S], start: usize, end: usize) {
+    let mut index = start;
+    while index < end {
+        let (data_index, remainder) = S::compute_data_index_and_remainder(index);
+        let element_end = (data_index + 1) * S::storage_size();
+        let stop = cmp::min(end, element_end);
+        let upper_mask = if stop == element_end {
+            S::MAX
+        }
+        else {
+            O::first_bits_mask((stop - data_index * S::storage_size()) as u32)
+        };
+        data[data_index] |= upper_mask & !O::first_bits_mask::<S>(remainder);
+        index = stop;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BitVector,Msb0};
+    use super::{RunLengthEncoded,RunLengthError};
+
+    fn create_bitvector_u8_14() -> BitVector<u8> {
+        "10110010010000".parse().unwrap()
+    }
+
+    #[test]
+    fn test_encode() {
+        let encoded = RunLengthEncoded::encode(&create_bitvector_u8_14());
+        assert_eq!(encoded.as_bytes(), [1, 1, 1, 2, 2, 1, 2, 1, 4]);
+        assert_eq!(encoded.len(), 14);
+
+        let encoded = RunLengthEncoded::encode(&BitVector::<u64>::with_capacity(300, false));
+        assert_eq!(encoded.as_bytes(), [0, 0b10101100, 0b00000010]);
+
+        let encoded = RunLengthEncoded::encode(&BitVector::<u8>::with_capacity(0, false));
+        assert_eq!(encoded.as_bytes(), [0]);
+        assert!(encoded.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let vec = create_bitvector_u8_14();
+        let decoded: BitVector<u8> = RunLengthEncoded::encode(&vec).decode();
+        assert_eq!(decoded.capacity(), 14);
+        assert_eq!(decoded.as_words(), vec.as_words());
+
+        let mut vec = BitVector::<u32, Msb0>::with_capacity(1000, false);
+        for index in (100..450).chain(451..452).chain(600..1000) {
+            vec.set(index, true);
+        }
+        let encoded = RunLengthEncoded::encode(&vec);
+        assert_eq!(encoded.as_bytes().len(), 10);
+
+        let decoded: BitVector<u32, Msb0> = encoded.decode();
+        assert_eq!(decoded.capacity(), 1000);
+        assert_eq!(decoded.as_words(), vec.as_words());
+    }
+
+    #[test]
+    fn test_decode_into_other_storage() {
+        let vec = create_bitvector_u8_14();
+        let decoded: BitVector<u64, Msb0> = RunLengthEncoded::encode(&vec).decode();
+        let decoded_iter_vec: Vec<_> = decoded.iter().collect();
+        let vec_iter_vec: Vec<_> = vec.iter().collect();
+        assert_eq!(decoded_iter_vec, vec_iter_vec);
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let encoded = RunLengthEncoded::from_bytes(vec![1, 1, 1, 2, 2, 1, 2, 1, 4], 14).unwrap();
+        assert_eq!(encoded.len(), 14);
+        let decoded: BitVector<u8> = encoded.decode();
+        assert_eq!(decoded.as_words(), create_bitvector_u8_14().as_words());
+
+        let encoded = RunLengthEncoded::from_bytes(vec![0, 0b10101100, 0b00000010], 1000).unwrap();
+        assert_eq!(encoded.len(), 300);
+    }
+
+    #[test]
+    fn test_from_invalid_bytes() {
+        assert_eq!(RunLengthEncoded::from_bytes(vec![], 1000), Err(RunLengthError::Empty));
+        assert_eq!(RunLengthEncoded::from_bytes(vec![2, 1], 1000), Err(RunLengthError::InvalidRunValue(2)));
+        assert_eq!(RunLengthEncoded::from_bytes(vec![0, 3, 0b10000000], 1000), Err(RunLengthError::InvalidRunLength { position: 2 }));
+        assert_eq!(RunLengthEncoded::from_bytes(vec![0, 3, 0], 1000), Err(RunLengthError::InvalidRunLength { position: 2 }));
+        assert_eq!(RunLengthEncoded::from_bytes(vec![0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02], 1000), Err(RunLengthError::InvalidRunLength { position: 1 }));
+    }
+
+    #[test]
+    fn test_from_bytes_exceeding_max_len() {
+        assert_eq!(RunLengthEncoded::from_bytes(vec![0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3f], 1 << 20), Err(RunLengthError::LengthExceeded { position: 1, max_len: 1 << 20 }));
+        assert_eq!(RunLengthEncoded::from_bytes(vec![1, 1, 1, 2, 2, 1, 2, 1, 4], 13), Err(RunLengthError::LengthExceeded { position: 8, max_len: 13 }));
+        assert_eq!(RunLengthEncoded::from_bytes(vec![0], 0).map(|encoded| encoded.len()), Ok(0));
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self,Deserialize,Deserializer,MapAccess,SeqAccess,Visitor};
+use serde::ser::{Serialize,SerializeStruct,Serializer};
+
+use BitStorage;
+use BitOrder;
+use BitVector;
+
+const FIELDS: &[&str] = &["len", "words"];
+
+// human-readable formats get a string of '0' and '1' characters, binary formats get the length and the raw storage elements
+impl<S: BitStorage + Serialize, O: BitOrder> Serialize for BitVector<S, O> {
+    fn serialize<R: Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        if serializer.is_human_readable() {
+            let bits: String = self.iter().map(|bit| if bit { '1' } else { '0' }).collect();
+            serializer.serialize_str(&bits)
+        }
+        else {
+            let mut state = serializer.serialize_struct("BitVector", 2)?;
+            state.serialize_field("len", &self.capacity())?;
+            state.serialize_field("words", self.as_words())?;
+            state.end()
+        }
+    }
+}
+
+impl<'de, S: BitStorage + Deserialize<'de>, O: BitOrder> Deserialize<'de> for BitVector<S, O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BitVector<S, O>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BitStringVisitor { phantom: PhantomData })
+        }
+        else {
+            deserializer.deserialize_struct("BitVector", FIELDS, WordsVisitor { phantom: PhantomData })
+        }
+    }
+}
+
+struct BitStringVisitor<S: BitStorage, O: BitOrder> {
+    phantom: PhantomData<BitVector<S, O>>
+}
+
+impl<'de, S: BitStorage, O: BitOrder> Visitor<'de> for BitStringVisitor<S, O> {
+    type Value = BitVector<S, O>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string of '0' and '1' characters")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<BitVector<S, O>, E> {
+        let mut bit_vector = BitVector::with_capacity(value.len(), false);
+        for (index, character) in value.chars().enumerate() {
+            match character {
+                '0' => {},
+                '1' => bit_vector.set(index, true),
+                _ => return Err(E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+        Ok(bit_vector)
+    }
+}
+
+struct WordsVisitor<S: BitStorage, O: BitOrder> {
+    phantom: PhantomData<BitVector<S, O>>
+}
+
+impl<S: BitStorage, O: BitOrder> WordsVisitor<S, O> {
+    fn validate<E: de::Error>(len: usize, words: Vec<S>) -> Result<BitVector<S, O>, E> {
+        let expected_words = len.div_ceil(S::storage_size());
+        if words.len() != expected_words {
+            return Err(E::invalid_length(words.len(), &&*format!("{} words for a length of {}", expected_words, len)));
+        }
+        let (data_index, remainder) = S::compute_data_index_and_remainder(len);
+        if data_index < words.len() && words[data_index] & !O::first_bits_mask::<S>(remainder) != S::ZERO {
+            return Err(E::custom(format!("padding bits past length {} are not zero", len)));
+        }
+        Ok(BitVector::from_vec(words, len))
+    }
+}
+
+impl<'de, S: BitStorage + Deserialize<'de>, O: BitOrder> Visitor<'de> for WordsVisitor<S, O> {
+    type Value = BitVector<S, O>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct BitVector")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BitVector<S, O>, A::Error> {
+        let len = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let words = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        WordsVisitor::validate(len, words)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<BitVector<S, O>, A::Error> {
+        let mut len = None;
+        let mut words = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "len" => len = Some(map.next_value()?),
+                "words" => words = Some(map.next_value()?),
+                _ => return Err(de::Error::unknown_field(&key, FIELDS))
+            }
+        }
+        let len = len.ok_or_else(|| de::Error::missing_field("len"))?;
+        let words = words.ok_or_else(|| de::Error::missing_field("words"))?;
+        WordsVisitor::validate(len, words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bincode;
+    use serde_json;
+
+    use super::super::{BitVector,Msb0};
+
+    fn create_bitvector_u8_10() -> BitVector<u8> {
+        let mut vec = BitVector::with_capacity(10, false);
+        vec.set(0, true);
+        vec.set(2, true);
+        vec.set(9, true);
+        vec
+    }
+
+    #[test]
+    fn test_serialize_human_readable() {
+        let vec = create_bitvector_u8_10();
+        assert_eq!(serde_json::to_string(&vec).unwrap(), "\"1010000001\"");
+    }
+
+    #[test]
+    fn test_deserialize_human_readable() {
+        let vec: BitVector<u32, Msb0> = serde_json::from_str("\"1010000001\"").unwrap();
+        let vec_iter_vec: Vec<_> = vec.iter().collect();
+        assert_eq!(vec_iter_vec, [true, false, true, false, false, false, false, false, false, true]);
+
+        assert!(serde_json::from_str::<BitVector<u32>>("\"1012\"").is_err());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let vec = create_bitvector_u8_10();
+        let bytes = bincode::serialize(&vec).unwrap();
+        let deserialized: BitVector<u8> = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(deserialized.capacity(), 10);
+        assert_eq!(deserialized.as_words(), vec.as_words());
+    }
+
+    #[test]
+    fn test_deserialize_wrong_word_count() {
+        let bytes = bincode::serialize(&(10usize, vec![0u8])).unwrap();
+        assert!(bincode::deserialize::<BitVector<u8>>(&bytes).is_err());
+
+        let bytes = bincode::serialize(&(10usize, vec![0u8, 0, 0])).unwrap();
+        assert!(bincode::deserialize::<BitVector<u8>>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_nonzero_padding() {
+        let bytes = bincode::serialize(&(10usize, vec![0u8, 0b00000100])).unwrap();
+        assert!(bincode::deserialize::<BitVector<u8>>(&bytes).is_err());
+        assert!(bincode::deserialize::<BitVector<u8, Msb0>>(&bytes).is_err());
+
+        let bytes = bincode::serialize(&(10usize, vec![0u8, 0b01000000])).unwrap();
+        assert!(bincode::deserialize::<BitVector<u8>>(&bytes).is_err());
+        assert!(bincode::deserialize::<BitVector<u8, Msb0>>(&bytes).is_ok());
+    }
+}
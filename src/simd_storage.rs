@@ -0,0 +1,303 @@
+use std::cmp;
+use std::ops::{BitAnd,BitAndAssign,BitOr,BitOrAssign,BitXor,BitXorAssign,Not,Shl,ShlAssign,Shr,ShrAssign};
+use std::simd::Simd;
+use std::simd::num::SimdUint;
+
+use bit_storage::private;
+use BitStorage;
+
+const LANES: usize = 4;
+const STORAGE_SIZE: usize = LANES * 64;
+
+/// A 256-bit storage element held in four `u64` lanes of a portable SIMD vector.
+///
+/// Lane 0 holds the least significant bits. The bulk operations of `BitVector<U64x4>` work a whole vector register at
+/// a time.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct U64x4(Simd<u64, LANES>);
+
+impl U64x4 {
+    #[inline]
+    pub fn from_array(lanes: [u64; LANES]) -> U64x4 {
+        U64x4(Simd::from_array(lanes))
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [u64; LANES] {
+        self.0.to_array()
+    }
+}
+
+impl From<Simd<u64, LANES>> for U64x4 {
+    fn from(vector: Simd<u64, LANES>) -> U64x4 {
+        U64x4(vector)
+    }
+}
+
+impl From<U64x4> for Simd<u64, LANES> {
+    fn from(element: U64x4) -> Simd<u64, LANES> {
+        element.0
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident) => {
+        impl $op for U64x4 {
+            type Output = U64x4;
+
+            #[inline]
+            fn $method(self, other: U64x4) -> U64x4 {
+                U64x4(self.0.$method(other.0))
+            }
+        }
+
+        impl $op_assign for U64x4 {
+            #[inline]
+            fn $method_assign(&mut self, other: U64x4) {
+                self.0.$method_assign(other.0);
+            }
+        }
+    }
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+impl Not for U64x4 {
+    type Output = U64x4;
+
+    #[inline]
+    fn not(self) -> U64x4 {
+        U64x4(!self.0)
+    }
+}
+
+impl Shl<u32> for U64x4 {
+    type Output = U64x4;
+
+    fn shl(self, amount: u32) -> U64x4 {
+        let (word_shift, bit_shift) = split_shift(amount);
+        let lanes = self.to_array();
+        let mut shifted = [0u64; LANES];
+        for index in word_shift..LANES {
+            shifted[index] = lanes[index - word_shift] << bit_shift;
+            if bit_shift != 0 && index > word_shift {
+                shifted[index] |= lanes[index - word_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        U64x4::from_array(shifted)
+    }
+}
+
+impl ShlAssign<u32> for U64x4 {
+    #[inline]
+    fn shl_assign(&mut self, amount: u32) {
+        *self = *self << amount;
+    }
+}
+
+impl Shr<u32> for U64x4 {
+    type Output = U64x4;
+
+    fn shr(self, amount: u32) -> U64x4 {
+        let (word_shift, bit_shift) = split_shift(amount);
+        let lanes = self.to_array();
+        let mut shifted = [0u64; LANES];
+        for index in 0..LANES - word_shift {
+            shifted[index] = lanes[index + word_shift] >> bit_shift;
+            if bit_shift != 0 && index + word_shift + 1 < LANES {
+                shifted[index] |= lanes[index + word_shift + 1] << (64 - bit_shift);
+            }
+        }
+        U64x4::from_array(shifted)
+    }
+}
+
+impl ShrAssign<u32> for U64x4 {
+    #[inline]
+    fn shr_assign(&mut self, amount: u32) {
+        *self = *self >> amount;
+    }
+}
+
+impl private::Sealed for U64x4 {}
+
+impl BitStorage for U64x4 {
+    type Bytes = [u8; STORAGE_SIZE / 8];
+
+    const ZERO: U64x4 = U64x4(Simd::from_array([0; LANES]));
+
+    const ONE: U64x4 = U64x4(Simd::from_array([1, 0, 0, 0]));
+
+    const MAX: U64x4 = U64x4(Simd::from_array([u64::MAX; LANES]));
+
+    #[inline]
+    fn storage_size() -> usize {
+        STORAGE_SIZE
+    }
+
+    #[inline]
+    fn count_ones(self) -> u32 {
+        self.0.count_ones().reduce_sum() as u32
+    }
+
+    fn leading_zeros(self) -> u32 {
+        let lanes = self.to_array();
+        match (0..LANES).rev().find(|&index| lanes[index] != 0) {
+            Some(index) => (LANES - 1 - index) as u32 * 64 + lanes[index].leading_zeros(),
+            None => STORAGE_SIZE as u32
+        }
+    }
+
+    fn trailing_zeros(self) -> u32 {
+        let lanes = self.to_array();
+        match (0..LANES).find(|&index| lanes[index] != 0) {
+            Some(index) => index as u32 * 64 + lanes[index].trailing_zeros(),
+            None => STORAGE_SIZE as u32
+        }
+    }
+
+    #[inline]
+    fn swap_bytes(self) -> U64x4 {
+        U64x4(self.0.swap_bytes().reverse())
+    }
+
+    #[inline]
+    fn reverse_bits(self) -> U64x4 {
+        U64x4(self.0.reverse_bits().reverse())
+    }
+
+    fn to_le_bytes(self) -> Self::Bytes {
+        let mut bytes = [0u8; STORAGE_SIZE / 8];
+        for (chunk, lane) in bytes.chunks_exact_mut(8).zip(self.to_array().iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_le_bytes(bytes: Self::Bytes) -> U64x4 {
+        let mut lanes = [0u64; LANES];
+        for (lane, chunk) in lanes.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut lane_bytes = [0u8; 8];
+            lane_bytes.copy_from_slice(chunk);
+            *lane = u64::from_le_bytes(lane_bytes);
+        }
+        U64x4::from_array(lanes)
+    }
+
+    fn mask(lo: usize, hi: usize) -> U64x4 {
+        if lo > hi || hi > STORAGE_SIZE {
+            panic!("Mask out of range. Storage size = {}, Low = {}, High = {}", STORAGE_SIZE, lo, hi);
+        }
+        let mut lanes = [0u64; LANES];
+        for (index, lane) in lanes.iter_mut().enumerate() {
+            let (start, end) = (index * 64, index * 64 + 64);
+            *lane = u64::mask(cmp::min(cmp::max(lo, start), end) - start, cmp::min(cmp::max(hi, start), end) - start);
+        }
+        U64x4::from_array(lanes)
+    }
+
+    #[inline]
+    fn to_u64(self) -> u64 {
+        self.to_array()[0]
+    }
+
+    #[inline]
+    fn from_u64(value: u64) -> U64x4 {
+        U64x4::from_array([value, 0, 0, 0])
+    }
+}
+
+// returns the shift in whole lanes and the remaining shift within a lane
+#[inline]
+fn split_shift(amount: u32) -> (usize, usize) {
+    if amount as usize >= STORAGE_SIZE {
+        panic!("Shift out of range. Storage size = {}, Shift = {}", STORAGE_SIZE, amount);
+    }
+    (amount as usize / 64, amount as usize % 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BitStorage,BitVector,Msb0};
+    use super::U64x4;
+
+    #[test]
+    fn test_shifts() {
+        let one = U64x4::from_array([1, 0, 0, 0]);
+        assert_eq!(one << 70, U64x4::from_array([0, 1 << 6, 0, 0]));
+        assert_eq!(one << 255, U64x4::from_array([0, 0, 0, 1 << 63]));
+
+        let element = U64x4::from_array([0, 0, 0b1011, 0]);
+        assert_eq!(element >> 66, U64x4::from_array([0b11 << 62, 0b10, 0, 0]));
+        assert_eq!(element << 0, element);
+    }
+
+    #[test]
+    #[should_panic(expected = "Shift out of range. Storage size = 256, Shift = 256")]
+    fn test_shift_out_of_range() {
+        let _ = U64x4::from_array([1, 0, 0, 0]) << 256;
+    }
+
+    #[test]
+    fn test_intrinsics() {
+        let element = U64x4::from_array([0, 0b1000, 0, 0b0110]);
+        assert_eq!(element.count_ones(), 3);
+        assert_eq!(element.trailing_zeros(), 67);
+        assert_eq!(element.leading_zeros(), 61);
+        assert_eq!(U64x4::from_array([0; 4]).trailing_zeros(), 256);
+        assert_eq!(element.reverse_bits(), U64x4::from_array([0b0110 << 60, 0, 1 << 60, 0]));
+        assert_eq!(element.swap_bytes().swap_bytes(), element);
+        assert_eq!(U64x4::from_le_bytes(element.to_le_bytes()), element);
+        assert_eq!(element.to_le_bytes()[8], 0b1000);
+        assert_eq!(U64x4::from_u64(0b1011).to_u64(), 0b1011);
+        assert_eq!((element >> 64).to_u64(), 0b1000);
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(U64x4::mask(60, 130), U64x4::from_array([0b1111 << 60, u64::MAX, 0b11, 0]));
+        assert_eq!(U64x4::mask(0, 256), U64x4::from_array([u64::MAX; 4]));
+        assert_eq!(U64x4::mask(100, 100), U64x4::from_array([0; 4]));
+    }
+
+    #[test]
+    fn test_bit_vector() {
+        let mut bit_vector = BitVector::<U64x4>::with_capacity(600, false);
+        for index in (0..600).step_by(7) {
+            bit_vector.set(index, true);
+        }
+        assert_eq!(bit_vector.count_ones(), 86);
+        assert_eq!(bit_vector.iter_ones().nth(40), Some(280));
+        assert_eq!(bit_vector.get_bits(252, 8), 0b10000001);
+
+        let mut other = BitVector::with_capacity(600, false);
+        for index in (0..600).step_by(3) {
+            other.set(index, true);
+        }
+        let mut and = bit_vector.clone();
+        and.and(&other);
+        assert_eq!(and.iter_ones().collect::<Vec<_>>(), (0..600).step_by(21).collect::<Vec<_>>());
+
+        let mut or = bit_vector.clone();
+        or.or(&other);
+        assert_eq!(or.count_ones(), 86 + 200 - 29);
+
+        or.fill(true);
+        assert_eq!(or.count_ones(), 600);
+        assert_eq!(or.as_words()[2], U64x4::from_array([u64::MAX, u64::MAX >> 40, 0, 0]));
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let mut bit_vector = BitVector::<U64x4, Msb0>::with_capacity(300, false);
+        bit_vector.set(0, true);
+        bit_vector.set(299, true);
+        let mut bytes = Vec::new();
+        bit_vector.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 32 + 64 + 4);
+        let read = BitVector::<U64x4, Msb0>::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.iter_ones().collect::<Vec<_>>(), [0, 299]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::ops::Index;
+
+use bit_slice::Iter;
+use BitStorage;
+use BitOrder;
+use Lsb0;
+use BitVector;
+use BitSlice;
+use BitSliceMut;
+
+use TRUE;
+use FALSE;
+
+/// A bit vector that keeps up to `INLINE` storage elements inline and only moves to a heap
+/// allocated `BitVector` once it grows past that.
+#[derive(Clone)]
+pub struct SmallBitVector<S: BitStorage, const INLINE: usize, O: BitOrder = Lsb0> {
+    storage: Storage<S, INLINE, O>
+}
+
+#[derive(Clone)]
+enum Storage<S: BitStorage, const INLINE: usize, O: BitOrder> {
+    Inline([S; INLINE], usize),
+    Heap(BitVector<S, O>)
+}
+
+impl<S: BitStorage, const INLINE: usize, O: BitOrder> SmallBitVector<S, INLINE, O> {
+    pub fn new() -> SmallBitVector<S, INLINE, O> {
+        SmallBitVector::with_capacity(0, false)
+    }
+
+    pub fn with_capacity(capacity: usize, default: bool) -> SmallBitVector<S, INLINE, O> {
+        let storage = if Self::fits_inline(capacity) {
+            let mut data = [if default { S::MAX } else { S::ZERO }; INLINE];
+            clear_unused_bits::<S, O>(&mut data, capacity);
+            Storage::Inline(data, capacity)
+        }
+        else {
+            Storage::Heap(BitVector::with_capacity(capacity, default))
+        };
+        SmallBitVector {
+            storage
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        match self.storage {
+            Storage::Inline(..) => self.as_bitslice().get(index),
+            Storage::Heap(ref bit_vector) => bit_vector.get(index)
+        }
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        match self.storage {
+            Storage::Inline(..) => self.as_bitslice_mut().set(index, value),
+            Storage::Heap(ref mut bit_vector) => bit_vector.set(index, value)
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        match self.storage {
+            Storage::Inline(_, capacity) => capacity,
+            Storage::Heap(ref bit_vector) => bit_vector.capacity()
+        }
+    }
+
+    pub fn resize(&mut self, new_capacity: usize, value: bool) {
+        if let Storage::Inline(data, capacity) = self.storage {
+            if !Self::fits_inline(new_capacity) {
+                self.storage = Storage::Heap(BitVector::from_vec(data.to_vec(), capacity));
+            }
+        }
+        match self.storage {
+            Storage::Inline(ref mut data, ref mut capacity) => {
+                if value && new_capacity > *capacity {
+                    let (data_index, remainder) = S::compute_data_index_and_remainder(*capacity);
+                    data[data_index] |= !O::first_bits_mask::<S>(remainder);
+                    for element in &mut data[data_index + 1..] {
+                        *element = S::MAX;
+                    }
+                }
+                *capacity = new_capacity;
+                clear_unused_bits::<S, O>(data, new_capacity);
+            },
+            Storage::Heap(ref mut bit_vector) => bit_vector.resize(new_capacity, value)
+        }
+    }
+
+    pub fn push(&mut self, value: bool) {
+        let capacity = self.capacity();
+        self.resize(capacity + 1, value);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.as_bitslice().count_ones()
+    }
+
+    pub fn is_inline(&self) -> bool {
+        match self.storage {
+            Storage::Inline(..) => true,
+            Storage::Heap(_) => false
+        }
+    }
+
+    pub fn split_at(&self, index: usize) -> (BitSlice<'_, S, O>, BitSlice<'_, S, O>) {
+        self.as_bitslice().split_at(index)
+    }
+
+    pub fn split_at_mut(&mut self, index: usize) -> (BitSliceMut<'_, S, O>, BitSliceMut<'_, S, O>) {
+        self.as_bitslice_mut().split_at_mut(index)
+    }
+
+    pub fn as_bitslice(&self) -> BitSlice<'_, S, O> {
+        match self.storage {
+            Storage::Inline(ref data, capacity) => unsafe { BitSlice::from_pointer(data.as_ptr(), capacity) },
+            Storage::Heap(ref bit_vector) => bit_vector.as_bitslice()
+        }
+    }
+
+    pub fn as_bitslice_mut(&mut self) -> BitSliceMut<'_, S, O> {
+        match self.storage {
+            Storage::Inline(ref mut data, capacity) => unsafe { BitSliceMut::from_pointer(data.as_mut_ptr(), capacity) },
+            Storage::Heap(ref mut bit_vector) => bit_vector.as_bitslice_mut()
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, S, O> {
+        self.as_bitslice().iter()
+    }
+
+    #[inline]
+    fn fits_inline(capacity: usize) -> bool {
+        capacity <= INLINE * S::storage_size()
+    }
+}
+
+#[inline]
+fn clear_unused_bits<S: BitStorage, O: BitOrder>(data: &mut [S], capacity: usize) {
+    let (data_index, remainder) = S::compute_data_index_and_remainder(capacity);
+    if data_index < data.len() {
+        data[data_index] &= O::first_bits_mask(remainder);
+        for element in &mut data[data_index + 1..] {
+            *element = S::ZERO;
+        }
+    }
+}
+
+impl<S: BitStorage, const INLINE: usize, O: BitOrder> Default for SmallBitVector<S, INLINE, O> {
+    fn default() -> SmallBitVector<S, INLINE, O> {
+        SmallBitVector::new()
+    }
+}
+
+impl<S: BitStorage, const INLINE: usize, O: BitOrder> From<SmallBitVector<S, INLINE, O>> for BitVector<S, O> {
+    fn from(small_bit_vector: SmallBitVector<S, INLINE, O>) -> BitVector<S, O> {
+        match small_bit_vector.storage {
+            Storage::Inline(data, capacity) => BitVector::from_vec(data.to_vec(), capacity),
+            Storage::Heap(bit_vector) => bit_vector
+        }
+    }
+}
+
+impl<S: BitStorage, const INLINE: usize, O: BitOrder> Index<usize> for SmallBitVector<S, INLINE, O> {
+    type Output = bool;
+
+    fn index(&self, index: usize) -> &bool {
+        bool_ref!(self.as_bitslice()[index])
+    }
+}
+
+impl<'a, S: BitStorage + 'a, const INLINE: usize, O: BitOrder> IntoIterator for &'a SmallBitVector<S, INLINE, O> {
+    type Item = bool;
+    type IntoIter = Iter<'a, S, O>;
+
+    fn into_iter(self) -> Iter<'a, S, O> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BitVector;
+    use super::SmallBitVector;
+
+    #[test]
+    fn test_with_capacity() {
+        let vec_64_2_false = SmallBitVector::<u64, 2>::with_capacity(128, false);
+        assert!(vec_64_2_false.is_inline());
+        assert!(vec_64_2_false.iter().all(|x| !x));
+
+        let vec_64_2_true = SmallBitVector::<u64, 2>::with_capacity(129, true);
+        assert!(!vec_64_2_true.is_inline());
+        assert!(vec_64_2_true.iter().all(|x| x));
+        assert_eq!(vec_64_2_true.count_ones(), 129);
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut vec = SmallBitVector::<u8, 2>::with_capacity(12, false);
+
+        vec.set(0, true);
+        vec.set(9, true);
+        vec.set(11, true);
+
+        assert_eq!(vec.get(0), Some(true));
+        assert_eq!(vec.get(1), Some(false));
+        assert_eq!(vec.get(9), Some(true));
+        assert_eq!(vec.get(12), None);
+        assert_eq!(vec[11], true);
+        assert_eq!(vec[10], false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_out_of_bounds() {
+        let mut vec = SmallBitVector::<u8, 2>::with_capacity(12, false);
+        vec.set(12, true);
+    }
+
+    #[test]
+    fn test_push_spills_to_heap() {
+        let mut vec = SmallBitVector::<u8, 2>::new();
+
+        for i in 0..16 {
+            vec.push(i % 2 == 0);
+        }
+        assert!(vec.is_inline());
+
+        vec.push(true);
+        assert!(!vec.is_inline());
+        assert_eq!(vec.capacity(), 17);
+        assert_eq!(vec.count_ones(), 9);
+
+        let iter_vec: Vec<_> = vec.iter().collect();
+        let expected: Vec<_> = (0..17).map(|i| i % 2 == 0).collect();
+        assert_eq!(iter_vec, expected);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut vec = SmallBitVector::<u8, 1>::with_capacity(4, true);
+
+        vec.resize(8, false);
+        assert!(vec.is_inline());
+        assert_eq!(vec.count_ones(), 4);
+
+        vec.resize(20, true);
+        assert!(!vec.is_inline());
+        assert_eq!(vec.count_ones(), 16);
+
+        vec.resize(2, false);
+        assert_eq!(vec.capacity(), 2);
+        assert_eq!(vec.count_ones(), 2);
+    }
+
+    #[test]
+    fn test_into_bit_vector() {
+        let mut inline = SmallBitVector::<u32, 1>::with_capacity(20, false);
+        inline.set(19, true);
+        let vec = BitVector::from(inline);
+        assert_eq!(vec.capacity(), 20);
+        assert_eq!(vec[19], true);
+        assert_eq!(vec.count_ones(), 1);
+
+        let mut heap = SmallBitVector::<u32, 1>::with_capacity(40, false);
+        heap.set(39, true);
+        let vec = BitVector::from(heap);
+        assert_eq!(vec.capacity(), 40);
+        assert_eq!(vec[39], true);
+        assert_eq!(vec.count_ones(), 1);
+    }
+
+    #[test]
+    fn test_split_at_mut() {
+        let mut vec = SmallBitVector::<u8, 2>::with_capacity(16, false);
+        {
+            let (mut left, mut right) = vec.split_at_mut(8);
+            left.set(7, true);
+            right.set(0, true);
+        }
+
+        let (left, right) = vec.split_at(8);
+        assert_eq!(left[7], true);
+        assert_eq!(right[0], true);
+        assert_eq!(vec.count_ones(), 2);
+    }
+}